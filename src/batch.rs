@@ -0,0 +1,158 @@
+//! # Batch Module
+//!
+//! Contains the bit-sliced batch evaluation of boolean circuits.
+
+use crate::{
+    circuit::{
+        CircuitExecutionError, CircuitMemory, CircuitMemoryError, GenericCircuit,
+        GenericCircuitExecutor,
+    },
+    gates::boolean::BitLanes,
+    model::{Component, Executable, SizedMemory},
+};
+use std::collections::HashMap;
+
+/// Packs up to `L::LANES` samples into lanes, sample `i` going to lane `i`.
+/// Lanes without a sample, and wires missing from a sample, are left unset.
+///
+/// # Panics
+///
+/// Panics if there are more samples than lanes.
+pub fn pack_samples<L: BitLanes>(samples: &[HashMap<usize, bool>]) -> HashMap<usize, L> {
+    assert!(
+        samples.len() <= L::LANES,
+        "{} samples do not fit in {} lanes",
+        samples.len(),
+        L::LANES
+    );
+
+    let mut lanes = HashMap::new();
+    for (lane, sample) in samples.iter().enumerate() {
+        for (&wire, &value) in sample {
+            let word = lanes.entry(wire).or_insert(L::splat(false));
+            *word = word.with_lane(lane, value);
+        }
+    }
+
+    lanes
+}
+
+/// Unpacks the first `count` lanes into one map of values per sample.
+pub fn unpack_lanes<L: BitLanes>(
+    lanes: &HashMap<usize, L>,
+    count: usize,
+) -> Vec<HashMap<usize, bool>> {
+    (0..count)
+        .map(|lane| {
+            lanes
+                .iter()
+                .map(|(&wire, &word)| (wire, word.lane(lane)))
+                .collect()
+        })
+        .collect()
+}
+
+/// Executes a boolean circuit on many samples, `L::LANES` samples per pass.
+/// Every wire holds an `L` value whose bits are independent evaluations.
+pub struct BatchExecutor<T, L = u64, M = CircuitMemory<L>> {
+    executor: GenericCircuitExecutor<T, L, M>,
+}
+
+impl<T, L> BatchExecutor<T, L>
+where
+    T: Component + Executable<L, CircuitMemory<L>>,
+    L: BitLanes,
+{
+    /// Creates a new batch executor over a `CircuitMemory`.
+    pub fn new(circuit: GenericCircuit<T, bool>) -> Self {
+        Self::with_memory(circuit)
+    }
+}
+
+impl<T, L, M> BatchExecutor<T, L, M>
+where
+    T: Component + Executable<L, M>,
+    L: BitLanes,
+    M: SizedMemory<L, Error = CircuitMemoryError>,
+{
+    /// Creates a new batch executor over the memory type `M`.
+    pub fn with_memory(circuit: GenericCircuit<T, bool>) -> Self {
+        Self {
+            executor: GenericCircuitExecutor::with_memory(circuit.with_value_type()),
+        }
+    }
+
+    /// Runs the circuit on every sample and returns the output values of each sample, in order.
+    /// Every sample must provide a value for each circuit input.
+    pub fn run(
+        &mut self,
+        samples: &[HashMap<usize, bool>],
+    ) -> Result<Vec<HashMap<usize, bool>>, CircuitExecutionError<T::Error>> {
+        let input_wires = self.executor.circuit().input_wires();
+        for sample in samples {
+            if sample.len() != input_wires.len() {
+                return Err(CircuitExecutionError::InputLengthMismatch);
+            }
+            if let Some(&wire) = input_wires.iter().find(|wire| !sample.contains_key(wire)) {
+                return Err(CircuitExecutionError::InputNotFoundError(wire));
+            }
+        }
+
+        let mut outputs = Vec::with_capacity(samples.len());
+        for chunk in samples.chunks(L::LANES) {
+            let lanes = self.executor.run(&pack_samples(chunk))?;
+            outputs.extend(unpack_lanes(&lanes, chunk.len()));
+        }
+
+        Ok(outputs)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::gates::boolean::tests::full_adder;
+
+    fn samples(count: usize) -> Vec<HashMap<usize, bool>> {
+        (0..count)
+            .map(|i| HashMap::from([(0, i % 2 == 1), (1, i % 3 == 1), (2, i % 5 == 1)]))
+            .collect()
+    }
+
+    #[test]
+    fn test_pack_and_unpack() {
+        let samples = samples(10);
+        let lanes = pack_samples::<u16>(&samples);
+
+        assert_eq!(lanes[&0], 0b1010101010);
+        assert_eq!(unpack_lanes(&lanes, samples.len()), samples);
+    }
+
+    #[test]
+    fn test_batch_matches_scalar_execution() {
+        // More samples than lanes, with a partial last pass
+        let samples = samples(150);
+
+        let mut scalar = GenericCircuitExecutor::new(full_adder());
+        let expected = scalar.run_many(&samples).unwrap();
+
+        let mut batch = BatchExecutor::<_, u64>::new(full_adder());
+        assert_eq!(batch.run(&samples).unwrap(), expected);
+
+        let mut batch = BatchExecutor::<_, u128>::new(full_adder());
+        assert_eq!(batch.run(&samples).unwrap(), expected);
+    }
+
+    #[test]
+    fn test_missing_sample_input() {
+        let mut samples = samples(3);
+        samples[1].remove(&2);
+        samples[1].insert(9, true);
+
+        let mut batch = BatchExecutor::<_, u64>::new(full_adder());
+        assert_eq!(
+            batch.run(&samples),
+            Err(CircuitExecutionError::InputNotFoundError(2))
+        );
+    }
+}
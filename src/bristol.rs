@@ -0,0 +1,515 @@
+//! # Bristol Module
+//!
+//! Contains the Bristol Fashion circuit format parser and writer.
+
+use crate::{
+    circuit::{CircuitBuilder, CircuitBuilderError, GenericCircuit},
+    model::Component,
+};
+use std::{
+    collections::{HashMap, HashSet},
+    fmt::Write,
+};
+use thiserror::Error;
+
+/// Gate operations of the Bristol Fashion format.
+/// `MAND` gates are expanded into one `And` per output when parsing.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BristolOperation {
+    And,
+    Xor,
+    Inv,
+    /// Assigns a constant value to the output wire.
+    Eq(bool),
+    /// Copies the input wire into the output wire.
+    Eqw,
+}
+
+/// Component that can be created from and converted to a Bristol Fashion gate.
+pub trait BristolComponent: Component + Sized {
+    /// Creates a component from a Bristol Fashion gate.
+    /// Returns `None` if the operation is not supported by the component.
+    fn from_bristol(op: BristolOperation, inputs: Vec<usize>, outputs: Vec<usize>) -> Option<Self>;
+
+    /// Returns the Bristol Fashion operation of the component, if any.
+    fn bristol_operation(&self) -> Option<BristolOperation>;
+}
+
+/// Boolean circuit along with its Bristol Fashion input and output grouping.
+#[derive(Debug, PartialEq, Eq)]
+pub struct BristolCircuit<T> {
+    /// Built circuit, with wires numbered as in the Bristol Fashion file.
+    pub circuit: GenericCircuit<T, bool>,
+    /// Number of wires of each input group.
+    pub input_groups: Vec<usize>,
+    /// Number of wires of each output group.
+    pub output_groups: Vec<usize>,
+}
+
+impl<T> BristolCircuit<T>
+where
+    T: BristolComponent,
+{
+    /// Parses a Bristol Fashion circuit.
+    /// Input wires read by no gate are kept as circuit inputs.
+    pub fn parse(source: &str) -> Result<Self, BristolError> {
+        let mut lines = source
+            .lines()
+            .enumerate()
+            .map(|(index, line)| (index + 1, line.split_whitespace().collect::<Vec<_>>()))
+            .filter(|(_, tokens)| !tokens.is_empty());
+
+        // Header: gate and wire counts, followed by the input and output groups
+        let (line, header) = lines.next().ok_or(BristolError::MissingHeader)?;
+        let [gate_count, wire_count] = parse_numbers(line, &header)?[..] else {
+            return Err(BristolError::InvalidLine(line));
+        };
+        let (line, inputs) = lines.next().ok_or(BristolError::MissingHeader)?;
+        let input_groups = parse_groups(line, &inputs)?;
+        let (line, outputs) = lines.next().ok_or(BristolError::MissingHeader)?;
+        let output_groups = parse_groups(line, &outputs)?;
+
+        let input_count = input_groups.iter().sum::<usize>();
+        let output_count = output_groups.iter().sum::<usize>();
+        if input_count + output_count > wire_count {
+            return Err(BristolError::InvalidLine(line));
+        }
+
+        let mut builder = CircuitBuilder::<T, bool>::new_deferred();
+        builder
+            .allow_unused_inputs(true)
+            .add_inputs(&(0..input_count).collect::<Vec<usize>>())
+            .add_outputs(&(wire_count - output_count..wire_count).collect::<Vec<usize>>());
+
+        let mut gates = 0;
+        for (line, tokens) in lines {
+            gates += 1;
+            for component in parse_gate::<T>(line, &tokens, wire_count)? {
+                builder.add_component(component)?;
+            }
+        }
+        if gates != gate_count {
+            return Err(BristolError::GateCountMismatch(gate_count, gates));
+        }
+
+        Ok(Self {
+            circuit: builder.build()?,
+            input_groups,
+            output_groups,
+        })
+    }
+
+    /// Writes the circuit in Bristol Fashion format.
+    /// Wires are renumbered so that inputs come first and outputs last, as the format requires.
+    /// Circuits whose memory slots are reused cannot be written, as their wires share slots.
+    pub fn write<W: Write>(&self, writer: &mut W) -> Result<(), BristolError> {
+        if self
+            .circuit
+            .releases()
+            .iter()
+            .any(|slots| !slots.is_empty())
+        {
+            return Err(BristolError::ReusedMemory);
+        }
+
+        let inputs = self.circuit.inputs();
+        let outputs = self.circuit.outputs();
+        if self.input_groups.iter().sum::<usize>() != inputs.len()
+            || self.output_groups.iter().sum::<usize>() != outputs.len()
+        {
+            return Err(BristolError::GroupMismatch);
+        }
+
+        let memory_map = self.circuit.memory_map();
+        let slot = |wire: &usize| {
+            memory_map
+                .get(wire)
+                .copied()
+                .ok_or(BristolError::UnmappedWire(*wire))
+        };
+
+        // Assign the Bristol wire numbers to the memory slots
+        let mut wires = HashMap::new();
+        for input in inputs {
+            let next = wires.len();
+            wires.insert(slot(input)?, next);
+        }
+        let output_slots = outputs.iter().map(slot).collect::<Result<Vec<_>, _>>()?;
+        let output_set = output_slots.iter().copied().collect::<HashSet<usize>>();
+        let mut internal_slots = memory_map
+            .values()
+            .filter(|slot| !wires.contains_key(slot) && !output_set.contains(slot))
+            .copied()
+            .collect::<Vec<usize>>();
+        internal_slots.sort_unstable();
+        internal_slots.dedup();
+        for slot in internal_slots.into_iter().chain(output_slots) {
+            let next = wires.len();
+            if wires.insert(slot, next).is_some() {
+                return Err(BristolError::OutputIsAnInput(slot));
+            }
+        }
+
+        writeln!(
+            writer,
+            "{} {}",
+            self.circuit.components().len(),
+            wires.len()
+        )?;
+        write_groups(writer, &self.input_groups)?;
+        write_groups(writer, &self.output_groups)?;
+        writeln!(writer)?;
+
+        let wire = |slot: &usize| {
+            wires
+                .get(slot)
+                .map(usize::to_string)
+                .ok_or(BristolError::UnmappedWire(*slot))
+        };
+        for (index, component) in self.circuit.components().iter().enumerate() {
+            let op = component
+                .bristol_operation()
+                .ok_or(BristolError::UnsupportedComponent(index))?;
+            let inputs = match op {
+                BristolOperation::Eq(value) => vec![usize::from(value).to_string()],
+                _ => component
+                    .inputs()
+                    .iter()
+                    .map(wire)
+                    .collect::<Result<_, _>>()?,
+            };
+            let outputs = component
+                .outputs()
+                .iter()
+                .map(wire)
+                .collect::<Result<Vec<String>, _>>()?;
+
+            writeln!(
+                writer,
+                "{} {} {} {} {}",
+                inputs.len(),
+                outputs.len(),
+                inputs.join(" "),
+                outputs.join(" "),
+                operation_name(op)
+            )?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Parses a gate line into one or more components.
+fn parse_gate<T: BristolComponent>(
+    line: usize,
+    tokens: &[&str],
+    wire_count: usize,
+) -> Result<Vec<T>, BristolError> {
+    let (name, fields) = tokens.split_last().ok_or(BristolError::InvalidLine(line))?;
+    let numbers = parse_numbers(line, fields)?;
+    let [input_count, output_count, ref wires @ ..] = numbers[..] else {
+        return Err(BristolError::InvalidLine(line));
+    };
+    if wires.len() != input_count + output_count {
+        return Err(BristolError::InvalidLine(line));
+    }
+    let (inputs, outputs) = wires.split_at(input_count);
+
+    // The input of an EQ gate is a constant rather than a wire
+    let mut gate_wires = outputs
+        .iter()
+        .chain(if *name == "EQ" { &[] } else { inputs });
+    if let Some(&wire) = gate_wires.find(|&&wire| wire >= wire_count) {
+        return Err(BristolError::WireOutOfRange(line, wire));
+    }
+
+    // Each entry holds the operation, its input wires and its output wires
+    let gates = match (*name, input_count, output_count) {
+        ("AND", 2, 1) => vec![(BristolOperation::And, inputs.to_vec(), outputs.to_vec())],
+        ("XOR", 2, 1) => vec![(BristolOperation::Xor, inputs.to_vec(), outputs.to_vec())],
+        ("INV", 1, 1) => vec![(BristolOperation::Inv, inputs.to_vec(), outputs.to_vec())],
+        ("EQW", 1, 1) => vec![(BristolOperation::Eqw, inputs.to_vec(), outputs.to_vec())],
+        ("EQ", 1, 1) => match inputs[0] {
+            0 | 1 => vec![(
+                BristolOperation::Eq(inputs[0] == 1),
+                vec![],
+                outputs.to_vec(),
+            )],
+            _ => return Err(BristolError::InvalidLine(line)),
+        },
+        ("MAND", _, _) if input_count == 2 * output_count => (0..output_count)
+            .map(|index| {
+                (
+                    BristolOperation::And,
+                    vec![inputs[index], inputs[output_count + index]],
+                    vec![outputs[index]],
+                )
+            })
+            .collect(),
+        _ => return Err(BristolError::UnsupportedGate(line, name.to_string())),
+    };
+
+    gates
+        .into_iter()
+        .map(|(op, inputs, outputs)| {
+            T::from_bristol(op, inputs, outputs)
+                .ok_or_else(|| BristolError::UnsupportedGate(line, name.to_string()))
+        })
+        .collect()
+}
+
+/// Parses a group line, made of the group count followed by the size of each group.
+fn parse_groups(line: usize, tokens: &[&str]) -> Result<Vec<usize>, BristolError> {
+    match parse_numbers(line, tokens)?.split_first() {
+        Some((&count, groups)) if count == groups.len() => Ok(groups.to_vec()),
+        _ => Err(BristolError::InvalidLine(line)),
+    }
+}
+
+fn parse_numbers(line: usize, tokens: &[&str]) -> Result<Vec<usize>, BristolError> {
+    tokens
+        .iter()
+        .map(|token| token.parse().map_err(|_| BristolError::InvalidLine(line)))
+        .collect()
+}
+
+fn write_groups<W: Write>(writer: &mut W, groups: &[usize]) -> Result<(), BristolError> {
+    write!(writer, "{}", groups.len())?;
+    for group in groups {
+        write!(writer, " {}", group)?;
+    }
+    writeln!(writer)?;
+    Ok(())
+}
+
+fn operation_name(op: BristolOperation) -> &'static str {
+    match op {
+        BristolOperation::And => "AND",
+        BristolOperation::Xor => "XOR",
+        BristolOperation::Inv => "INV",
+        BristolOperation::Eq(_) => "EQ",
+        BristolOperation::Eqw => "EQW",
+    }
+}
+
+#[derive(Debug, Error, PartialEq, Eq)]
+pub enum BristolError {
+    #[error("Missing circuit header")]
+    MissingHeader,
+    #[error("Invalid line {0}")]
+    InvalidLine(usize),
+    #[error("Unsupported gate {1} at line {0}")]
+    UnsupportedGate(usize, String),
+    #[error("Wire {1} at line {0} is out of range")]
+    WireOutOfRange(usize, usize),
+    #[error("Expected {0} gates, found {1}")]
+    GateCountMismatch(usize, usize),
+    #[error("Input and output groups do not match the circuit")]
+    GroupMismatch,
+    #[error("Wire {0} is not mapped to memory")]
+    UnmappedWire(usize),
+    #[error("Memory slot {0} is both a circuit input and output")]
+    OutputIsAnInput(usize),
+    #[error("Memory slots are reused by several wires")]
+    ReusedMemory,
+    #[error("Component {0} has no Bristol Fashion operation")]
+    UnsupportedComponent(usize),
+    #[error("Circuit builder error: {0}")]
+    BuilderError(#[from] CircuitBuilderError),
+    #[error("Formatting error")]
+    FormatError(#[from] std::fmt::Error),
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        circuit::{CircuitMemory, GenericCircuitExecutor},
+        model::{Executable, Memory},
+    };
+
+    #[derive(Debug, PartialEq, Eq, Clone)]
+    struct Gate {
+        op: BristolOperation,
+        inputs: Vec<usize>,
+        outputs: Vec<usize>,
+    }
+
+    impl Component for Gate {
+        fn inputs(&self) -> &[usize] {
+            &self.inputs
+        }
+
+        fn outputs(&self) -> &[usize] {
+            &self.outputs
+        }
+
+        fn set_inputs(&mut self, inputs: Vec<usize>) {
+            self.inputs = inputs;
+        }
+
+        fn set_outputs(&mut self, outputs: Vec<usize>) {
+            self.outputs = outputs;
+        }
+    }
+
+    impl Executable<bool, CircuitMemory<bool>> for Gate {
+        type Error = crate::circuit::CircuitMemoryError;
+
+        fn execute(&self, memory: &mut CircuitMemory<bool>) -> Result<(), Self::Error> {
+            let read = |position: usize| memory.read(self.inputs[position]);
+            let result = match self.op {
+                BristolOperation::And => read(0)? && read(1)?,
+                BristolOperation::Xor => read(0)? ^ read(1)?,
+                BristolOperation::Inv => !read(0)?,
+                BristolOperation::Eq(value) => value,
+                BristolOperation::Eqw => read(0)?,
+            };
+            memory.write(self.outputs[0], result)
+        }
+    }
+
+    impl BristolComponent for Gate {
+        fn from_bristol(
+            op: BristolOperation,
+            inputs: Vec<usize>,
+            outputs: Vec<usize>,
+        ) -> Option<Self> {
+            Some(Self {
+                op,
+                inputs,
+                outputs,
+            })
+        }
+
+        fn bristol_operation(&self) -> Option<BristolOperation> {
+            Some(self.op)
+        }
+    }
+
+    const FULL_ADDER: &str = "5 8
+3 1 1 1
+1 2
+
+2 1 0 1 3 XOR
+2 1 3 2 6 XOR
+2 1 0 1 4 AND
+2 1 3 2 5 AND
+2 1 4 5 7 XOR
+";
+
+    #[test]
+    fn test_parse_and_execute() {
+        let bristol = BristolCircuit::<Gate>::parse(FULL_ADDER).unwrap();
+        assert_eq!(bristol.input_groups, vec![1, 1, 1]);
+        assert_eq!(bristol.output_groups, vec![2]);
+        assert_eq!(bristol.circuit.outputs(), &[6, 7]);
+
+        let mut executor = GenericCircuitExecutor::new(bristol.circuit);
+        let output = executor
+            .run(&HashMap::from([(0, true), (1, true), (2, true)]))
+            .unwrap();
+
+        assert_eq!(output, HashMap::from([(6, true), (7, true)]));
+    }
+
+    #[test]
+    fn test_write_round_trip() {
+        let bristol = BristolCircuit::<Gate>::parse(FULL_ADDER).unwrap();
+
+        let mut written = String::new();
+        bristol.write(&mut written).unwrap();
+
+        assert_eq!(written, FULL_ADDER);
+    }
+
+    #[test]
+    fn test_parse_constants_and_mand() {
+        let source = "3 7
+1 2
+1 2
+1 1 1 2 EQ
+1 1 0 3 INV
+4 2 0 1 2 3 5 6 MAND
+";
+        let bristol = BristolCircuit::<Gate>::parse(source).unwrap();
+        assert_eq!(bristol.circuit.components().len(), 4);
+
+        let mut executor = GenericCircuitExecutor::new(bristol.circuit);
+        let output = executor
+            .run(&HashMap::from([(0, false), (1, true)]))
+            .unwrap();
+
+        // 5 = 0 AND 2, 6 = 1 AND 3
+        assert_eq!(output, HashMap::from([(5, false), (6, true)]));
+    }
+
+    #[test]
+    fn test_parse_unused_input() {
+        let source = "1 4\n1 3\n1 1\n2 1 0 1 3 AND\n";
+        let bristol = BristolCircuit::<Gate>::parse(source).unwrap();
+        assert_eq!(bristol.circuit.inputs(), &[0, 1, 2]);
+
+        let mut written = String::new();
+        bristol.write(&mut written).unwrap();
+        assert_eq!(written, "1 4\n1 3\n1 1\n\n2 1 0 1 3 AND\n");
+
+        let mut executor = GenericCircuitExecutor::new(bristol.circuit);
+        let output = executor
+            .run(&HashMap::from([(0, true), (1, true), (2, false)]))
+            .unwrap();
+
+        assert_eq!(output, HashMap::from([(3, true)]));
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        assert_eq!(
+            BristolCircuit::<Gate>::parse(""),
+            Err(BristolError::MissingHeader)
+        );
+        assert_eq!(
+            BristolCircuit::<Gate>::parse("1 3\n1 2\n1 1\n2 1 0 1 2 NAND"),
+            Err(BristolError::UnsupportedGate(4, "NAND".to_string()))
+        );
+        assert_eq!(
+            BristolCircuit::<Gate>::parse("1 3\n1 2\n1 1\n2 1 0 4 2 AND"),
+            Err(BristolError::WireOutOfRange(4, 4))
+        );
+        assert_eq!(
+            BristolCircuit::<Gate>::parse("2 3\n1 2\n1 1\n2 1 0 1 2 AND"),
+            Err(BristolError::GateCountMismatch(2, 1))
+        );
+    }
+
+    #[test]
+    fn test_write_errors() {
+        let mut bristol = BristolCircuit::<Gate>::parse(FULL_ADDER).unwrap();
+        bristol.circuit.reuse_memory_slots();
+        assert_eq!(
+            bristol.write(&mut String::new()),
+            Err(BristolError::ReusedMemory)
+        );
+
+        // Slot 3 is read by the gate but mapped to no wire
+        let gate = Gate {
+            op: BristolOperation::And,
+            inputs: vec![0, 3],
+            outputs: vec![2],
+        };
+        let bristol = BristolCircuit {
+            circuit: GenericCircuit::new(
+                vec![gate],
+                HashMap::from([(0, 0), (1, 1), (2, 2)]),
+                vec![0, 1],
+                vec![2],
+            ),
+            input_groups: vec![2],
+            output_groups: vec![1],
+        };
+        assert_eq!(
+            bristol.write(&mut String::new()),
+            Err(BristolError::UnmappedWire(3))
+        );
+    }
+}
@@ -2,20 +2,32 @@
 //!
 //! Contains the generic circuit implementation based on the model traits.
 
-use crate::model::*;
+#[cfg(feature = "serde")]
+use crate::serialization::DataError;
+use crate::{
+    debugger::Debugger,
+    model::*,
+    observer::{NoopObserver, Observation},
+    stats::CircuitStats,
+};
 use std::{
-    collections::{HashMap, HashSet},
+    cmp::Reverse,
+    collections::{hash_map::Entry, BinaryHeap, HashMap, HashSet},
+    hash::{DefaultHasher, Hash, Hasher},
     marker::PhantomData,
+    panic, thread,
 };
 use thiserror::Error;
 
 /// Circuit memory generic over the stored value type.
 #[derive(Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct CircuitMemory<T> {
     wires: Vec<Option<T>>,
 }
 
 impl<T> CircuitMemory<T> {
+    /// Creates a new memory with the specified number of empty slots.
     pub fn new(size: usize) -> Self {
         let mut wires = Vec::with_capacity(size);
 
@@ -25,6 +37,30 @@ impl<T> CircuitMemory<T> {
 
         Self { wires }
     }
+
+    /// Clears every memory slot, keeping the allocation for the next execution.
+    pub fn clear(&mut self) {
+        self.wires.iter_mut().for_each(|slot| *slot = None);
+    }
+}
+
+impl<T> SizedMemory<T> for CircuitMemory<T>
+where
+    T: Copy,
+{
+    fn with_size(size: usize) -> Self {
+        Self::new(size)
+    }
+
+    fn clear(&mut self) {
+        CircuitMemory::clear(self);
+    }
+
+    fn release(&mut self, index: usize) {
+        if let Some(slot) = self.wires.get_mut(index) {
+            *slot = None;
+        }
+    }
 }
 
 impl<T> Memory<T> for CircuitMemory<T>
@@ -62,19 +98,32 @@ where
 
 /// Circuit builder generic over the component type and the stored value type.
 #[derive(Default, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct CircuitBuilder<T, U> {
     components: Vec<T>,
     circuit_inputs: Vec<usize>,
+    /// Circuit inputs, for constant-time lookups.
+    input_set: HashSet<usize>,
+    circuit_outputs: Vec<usize>,
     component_inputs: HashSet<usize>,
     component_outputs: HashSet<usize>,
     index_map: HashMap<usize, usize>,
     next_index: usize,
+    deferred: Option<Vec<T>>,
+    allow_unused_inputs: bool,
+    /// Positions of the added components, by structural hash.
+    /// The hashes are not stable across builds, so the index is rebuilt after loading.
+    #[cfg_attr(feature = "serde", serde(skip))]
+    structural_index: HashMap<u64, Vec<usize>>,
+    /// Number of components recorded in the structural index.
+    #[cfg_attr(feature = "serde", serde(skip))]
+    indexed_components: usize,
     _phantom: PhantomData<U>,
 }
 
 impl<T, U> CircuitBuilder<T, U>
 where
-    T: Component + Executable<U, CircuitMemory<U>>,
+    T: Component,
     U: Copy,
 {
     /// Creates a new circuit builder.
@@ -82,29 +131,78 @@ where
         Self {
             components: Vec::new(),
             circuit_inputs: Vec::new(),
+            input_set: HashSet::new(),
+            circuit_outputs: Vec::new(),
             index_map: HashMap::new(),
             component_inputs: HashSet::new(),
             component_outputs: HashSet::new(),
             next_index: 0,
+            deferred: None,
+            allow_unused_inputs: false,
+            structural_index: HashMap::new(),
+            indexed_components: 0,
             _phantom: PhantomData,
         }
     }
 
+    /// Creates a new circuit builder that accepts components in any order.
+    /// Components are collected as they are added and topologically sorted on `build`.
+    pub fn new_deferred() -> Self {
+        Self {
+            deferred: Some(Vec::new()),
+            ..Self::new()
+        }
+    }
+
     /// Adds circuit inputs to the builder.
     pub fn add_inputs(&mut self, inputs: &[usize]) -> &mut Self {
         self.circuit_inputs.extend(inputs.iter().copied());
+        self.input_set.extend(inputs.iter().copied());
+        self
+    }
+
+    /// Sets whether circuit inputs read by no component are accepted, such as the unused
+    /// inputs of a circuit loaded from a file. Unused inputs are an error by default.
+    pub fn allow_unused_inputs(&mut self, allow: bool) -> &mut Self {
+        self.allow_unused_inputs = allow;
+        self
+    }
+
+    /// Declares circuit outputs in the builder, keeping the declaration order.
+    /// Any driven wire can be an output, including wires consumed by other components.
+    /// If no outputs are declared, the wires not consumed by any component are used.
+    pub fn add_outputs(&mut self, outputs: &[usize]) -> &mut Self {
+        self.circuit_outputs.extend(outputs.iter().copied());
         self
     }
 
     /// Adds a component to the builder.
-    pub fn add_component(&mut self, mut component: T) -> Result<&mut Self, CircuitBuilderError> {
-        if component.inputs().is_empty() || component.outputs().is_empty() {
+    /// Components without inputs, such as constants, are accepted as long as they have outputs.
+    /// In deferred mode the component is only validated once the circuit is built.
+    pub fn add_component(&mut self, component: T) -> Result<&mut Self, CircuitBuilderError> {
+        if component.outputs().is_empty() {
             return Err(CircuitBuilderError::DisconnectedComponent);
         }
 
+        match self.deferred.as_mut() {
+            Some(pending) => pending.push(component),
+            None => self.insert_component(component)?,
+        }
+
+        Ok(self)
+    }
+
+    /// Returns whether an added component drives the wire.
+    /// In deferred mode components are only checked once the circuit is built.
+    pub(crate) fn drives(&self, wire: usize) -> bool {
+        self.component_outputs.contains(&wire)
+    }
+
+    /// Reindexes the component wires and appends it to the execution order.
+    fn insert_component(&mut self, mut component: T) -> Result<(), CircuitBuilderError> {
         let mut reindexed_inputs = Vec::new();
         for &input in component.inputs() {
-            if !self.component_outputs.contains(&input) && !self.circuit_inputs.contains(&input) {
+            if !self.component_outputs.contains(&input) && !self.input_set.contains(&input) {
                 return Err(CircuitBuilderError::TopologicalOrderError(input));
             }
 
@@ -119,7 +217,7 @@ where
 
         let mut reindexed_outputs = Vec::new();
         for &output in component.outputs() {
-            if self.circuit_inputs.contains(&output) {
+            if self.input_set.contains(&output) {
                 return Err(CircuitBuilderError::OutputIsACircuitInput(output));
             }
             if self.component_outputs.contains(&output) {
@@ -139,32 +237,66 @@ where
         component.set_outputs(reindexed_outputs);
         self.components.push(component);
 
-        Ok(self)
+        Ok(())
     }
 
     /// Builds the circuit.
-    pub fn build(self) -> Result<GenericCircuit<T, U>, CircuitBuilderError> {
+    pub fn build(mut self) -> Result<GenericCircuit<T, U>, CircuitBuilderError> {
+        if let Some(pending) = self.deferred.take() {
+            for component in topological_sort(pending, &self.input_set)? {
+                self.insert_component(component)?;
+            }
+        }
+
         if self.components.is_empty() {
             return Err(CircuitBuilderError::EmptyBuilder);
         }
 
-        // Validate that all inputs are used by at least one component
-        let unused_inputs = self
+        // Validate that all inputs are used by at least one component or declared as outputs
+        let unread_inputs = self
             .circuit_inputs
             .iter()
             .filter(|input| !self.component_inputs.contains(input))
             .copied()
             .collect::<Vec<usize>>();
-        if !unused_inputs.is_empty() {
+        let unused_inputs = unread_inputs
+            .iter()
+            .filter(|input| !self.circuit_outputs.contains(input))
+            .copied()
+            .collect::<Vec<usize>>();
+        if !unused_inputs.is_empty() && !self.allow_unused_inputs {
             return Err(CircuitBuilderError::UnusedInputs(unused_inputs));
         }
 
-        // Determine the circuit outputs
-        let circuit_outputs = self
-            .component_outputs
-            .difference(&self.component_inputs)
-            .copied()
-            .collect::<Vec<usize>>();
+        // Inputs read by no component still need a memory slot to be written to
+        for input in unread_inputs {
+            self.index_map.entry(input).or_insert_with(|| {
+                let index = self.next_index;
+                self.next_index += 1;
+                index
+            });
+        }
+
+        // Validate that all declared outputs are driven
+        if let Some(&undriven) = self.circuit_outputs.iter().find(|output| {
+            !self.component_outputs.contains(output) && !self.input_set.contains(output)
+        }) {
+            return Err(CircuitBuilderError::UndrivenOutput(undriven));
+        }
+
+        // Determine the circuit outputs, ordered by their memory index when not declared.
+        // Merged wires share a memory index, so ties are ordered by wire id.
+        let circuit_outputs = if self.circuit_outputs.is_empty() {
+            let mut outputs = self
+                .component_outputs
+                .difference(&self.component_inputs)
+                .copied()
+                .collect::<Vec<usize>>();
+            outputs.sort_unstable_by_key(|&output| (self.index_map.get(&output), output));
+            outputs
+        } else {
+            self.circuit_outputs
+        };
 
         Ok(GenericCircuit::new(
             self.components,
@@ -175,55 +307,337 @@ where
     }
 }
 
+impl<T, U> CircuitBuilder<T, U>
+where
+    T: StructuralHash,
+    U: Copy,
+{
+    /// Adds a component to the builder, merging it with an already added component computing
+    /// the same function of the same wires.
+    /// The outputs of a merged component are mapped to the memory slots of the existing one,
+    /// so downstream consumers read them instead.
+    /// In deferred mode components are not merged; use the structural hashing pass instead.
+    pub fn add_hashed_component(&mut self, component: T) -> Result<&mut Self, CircuitBuilderError> {
+        if self.deferred.is_some() {
+            return self.add_component(component);
+        }
+
+        let hash = |signature: &(T::Key, Vec<usize>, usize)| {
+            let mut hasher = DefaultHasher::new();
+            signature.hash(&mut hasher);
+            hasher.finish()
+        };
+
+        // Index the components added since the last call, or since the builder was loaded
+        for position in self.indexed_components..self.components.len() {
+            let added = &self.components[position];
+            let signature = structural_signature(added, added.inputs());
+            self.structural_index
+                .entry(hash(&signature))
+                .or_default()
+                .push(position);
+        }
+        self.indexed_components = self.components.len();
+
+        // Components reading a wire without a memory slot cannot match any added component
+        let inputs = component
+            .inputs()
+            .iter()
+            .map(|input| self.index_map.get(input).copied())
+            .collect::<Option<Vec<_>>>();
+        let existing = inputs.and_then(|inputs| {
+            let signature = structural_signature(&component, &inputs);
+            self.structural_index
+                .get(&hash(&signature))?
+                .iter()
+                .copied()
+                .find(|&position| {
+                    let existing = &self.components[position];
+                    structural_signature(existing, existing.inputs()) == signature
+                })
+        });
+        let Some(position) = existing else {
+            return self.add_component(component);
+        };
+
+        for &output in component.outputs() {
+            if self.input_set.contains(&output) {
+                return Err(CircuitBuilderError::OutputIsACircuitInput(output));
+            }
+            if self.component_outputs.contains(&output) {
+                return Err(CircuitBuilderError::OutputsConnection(output));
+            }
+        }
+
+        self.component_inputs.extend(component.inputs());
+        for (&output, &slot) in component
+            .outputs()
+            .iter()
+            .zip(self.components[position].outputs())
+        {
+            self.component_outputs.insert(output);
+            self.index_map.insert(output, slot);
+        }
+
+        Ok(self)
+    }
+}
+
+/// Returns what identifies the function computed by a component reading the given wires:
+/// its structural key, its inputs, sorted when commutative, and its number of outputs.
+pub(crate) fn structural_signature<T: StructuralHash>(
+    component: &T,
+    inputs: &[usize],
+) -> (T::Key, Vec<usize>, usize) {
+    let mut inputs = inputs.to_vec();
+    if component.is_commutative() {
+        inputs.sort_unstable();
+    }
+
+    (
+        component.structural_key(),
+        inputs,
+        component.outputs().len(),
+    )
+}
+
+/// Sorts components so that every wire is produced before it is consumed.
+/// Components that are already in order keep their relative position.
+fn topological_sort<T: Component>(
+    components: Vec<T>,
+    circuit_inputs: &HashSet<usize>,
+) -> Result<Vec<T>, CircuitBuilderError> {
+    // Map every wire to the component producing it
+    let mut producers = HashMap::new();
+    for (index, component) in components.iter().enumerate() {
+        for &output in component.outputs() {
+            if producers.insert(output, index).is_some() {
+                return Err(CircuitBuilderError::OutputsConnection(output));
+            }
+        }
+    }
+
+    // Inputs that are neither circuit inputs nor produced by any component
+    let mut dangling_inputs = components
+        .iter()
+        .flat_map(|component| component.inputs())
+        .filter(|input| !producers.contains_key(input) && !circuit_inputs.contains(input))
+        .copied()
+        .collect::<Vec<usize>>();
+    if !dangling_inputs.is_empty() {
+        dangling_inputs.sort_unstable();
+        dangling_inputs.dedup();
+        return Err(CircuitBuilderError::DanglingInputs(dangling_inputs));
+    }
+
+    let mut pending_inputs = vec![0; components.len()];
+    let mut consumers = vec![Vec::new(); components.len()];
+    for (index, component) in components.iter().enumerate() {
+        for input in component.inputs() {
+            if let Some(&producer) = producers.get(input) {
+                pending_inputs[index] += 1;
+                consumers[producer].push(index);
+            }
+        }
+    }
+
+    // Kahn's algorithm, always picking the earliest added ready component
+    let mut ready = pending_inputs
+        .iter()
+        .enumerate()
+        .filter(|(_, &count)| count == 0)
+        .map(|(index, _)| Reverse(index))
+        .collect::<BinaryHeap<Reverse<usize>>>();
+    let mut order = Vec::with_capacity(components.len());
+    while let Some(Reverse(index)) = ready.pop() {
+        order.push(index);
+        for &consumer in &consumers[index] {
+            pending_inputs[consumer] -= 1;
+            if pending_inputs[consumer] == 0 {
+                ready.push(Reverse(consumer));
+            }
+        }
+    }
+
+    if order.len() < components.len() {
+        let start = pending_inputs
+            .iter()
+            .position(|&count| count > 0)
+            .unwrap_or_default();
+        return Err(CircuitBuilderError::CycleError(find_cycle(
+            &components,
+            &producers,
+            &pending_inputs,
+            start,
+        )));
+    }
+
+    let mut slots = components.into_iter().map(Some).collect::<Vec<Option<T>>>();
+    Ok(order
+        .into_iter()
+        .filter_map(|index| slots[index].take())
+        .collect())
+}
+
+/// Walks backwards from an unsorted component until a component repeats.
+/// Returns the wires forming the cycle in execution order.
+fn find_cycle<T: Component>(
+    components: &[T],
+    producers: &HashMap<usize, usize>,
+    pending_inputs: &[usize],
+    start: usize,
+) -> Vec<usize> {
+    let mut visited = HashMap::new();
+    let mut wires = Vec::new();
+    let mut current = start;
+
+    while let Entry::Vacant(entry) = visited.entry(current) {
+        entry.insert(wires.len());
+
+        // Every unsorted component consumes at least one wire from another unsorted component
+        let Some((wire, producer)) = components[current].inputs().iter().find_map(|input| {
+            producers
+                .get(input)
+                .filter(|&&producer| pending_inputs[producer] > 0)
+                .map(|&producer| (*input, producer))
+        }) else {
+            break;
+        };
+
+        wires.push(wire);
+        current = producer;
+    }
+
+    let mut cycle = wires.split_off(visited.get(&current).copied().unwrap_or_default());
+    cycle.reverse();
+    cycle
+}
+
 /// Represents a generic circuit with a topological, linear execution order.
 /// Utilizes a generic memory to store wire values and execute gates.
 #[derive(Debug, PartialEq, Eq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(
+        try_from = "CircuitData<T, U>",
+        bound(deserialize = "T: Component + serde::Deserialize<'de>, U: Copy")
+    )
+)]
 pub struct GenericCircuit<T, U> {
     components: Vec<T>,
     inputs: Vec<usize>,
     outputs: Vec<usize>,
+    input_wires: Vec<usize>,
+    output_wires: Vec<usize>,
     memory_map: HashMap<usize, usize>,
+    /// Memory slots released after each component, when slots are reused.
+    releases: Vec<Vec<usize>>,
+    /// Memory layout before slots were reused, where every wire has its own slot.
+    wire_layout: Option<WireLayout>,
+    /// Number of memory slots, computed when the circuit is built or rewritten.
+    memory_size: usize,
+    /// Topological level of each component, computed when the circuit is built or rewritten.
+    #[cfg_attr(feature = "serde", serde(skip))]
+    levels: Vec<usize>,
     _phantom: PhantomData<U>,
 }
 
+/// Memory layout of a circuit whose wires each have their own memory slot, kept by the memory
+/// reuse pass to identify the wires sharing a slot.
+#[derive(Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub(crate) struct WireLayout {
+    /// Memory slots read and written by each component, in execution order.
+    pub(crate) slots: Vec<(Vec<usize>, Vec<usize>)>,
+    /// Map from original wire ids to memory slots.
+    pub(crate) memory_map: HashMap<usize, usize>,
+}
+
 impl<T, U> Component for GenericCircuit<T, U>
 where
-    T: Component + Executable<U, CircuitMemory<U>>,
+    T: Component,
     U: Copy,
 {
     /// Returns the indices of the input nodes for the entire circuit.
+    /// When mounted in a parent circuit, these are the parent wires bound to the circuit inputs.
     fn inputs(&self) -> &[usize] {
         &self.inputs
     }
 
     /// Returns the indices of the output nodes for the entire circuit.
+    /// When mounted in a parent circuit, these are the parent wires bound to the circuit outputs.
     fn outputs(&self) -> &[usize] {
         &self.outputs
     }
 
-    /// Sets the input nodes for the entire circuit.
+    /// Binds the circuit inputs, in order, to the provided parent wires.
     fn set_inputs(&mut self, inputs: Vec<usize>) {
         self.inputs = inputs;
     }
 
-    /// Sets the output nodes for the entire circuit.
+    /// Binds the circuit outputs, in order, to the provided parent wires.
     fn set_outputs(&mut self, outputs: Vec<usize>) {
         self.outputs = outputs;
     }
 }
 
-impl<T, U> Executable<U, CircuitMemory<U>> for GenericCircuit<T, U>
+impl<T, U> Kind for GenericCircuit<T, U>
 where
-    T: Component + Executable<U, CircuitMemory<U>>,
+    T: Component + Kind,
+    U: Copy,
+{
+    fn kind(&self) -> &str {
+        "CIRCUIT"
+    }
+
+    /// A circuit is multiplicative if any of its components is.
+    fn is_multiplicative(&self) -> bool {
+        self.components.iter().any(Kind::is_multiplicative)
+    }
+
+    fn nested_stats(&self) -> Option<CircuitStats> {
+        Some(self.kind_stats())
+    }
+}
+
+impl<T, U, M> Executable<U, M> for GenericCircuit<T, U>
+where
+    T: Component + Executable<U, M>,
     U: Copy,
+    M: SizedMemory<U, Error = CircuitMemoryError>,
 {
-    type Error = CircuitExecutionError;
+    type Error = CircuitExecutionError<T::Error>;
+
+    /// Executes the circuit as a component of a parent circuit.
+    /// Input values are copied from the parent memory into a scratch memory, and output
+    /// values are copied back once all components have run.
+    fn execute(&self, memory: &mut M) -> Result<(), Self::Error> {
+        if self.inputs.len() != self.input_wires.len() {
+            return Err(CircuitExecutionError::InputLengthMismatch);
+        }
+        if self.outputs.len() != self.output_wires.len() {
+            return Err(CircuitExecutionError::OutputLengthMismatch);
+        }
+
+        let mut scratch = M::with_size(self.memory_size());
+
+        for (&port, input) in self.inputs.iter().zip(&self.input_wires) {
+            let internal_index = self
+                .memory_map
+                .get(input)
+                .ok_or(CircuitExecutionError::MemoryMappingError(*input))?;
+            scratch.write(*internal_index, memory.read(port)?)?;
+        }
 
-    fn execute(&self, memory: &mut CircuitMemory<U>) -> Result<(), CircuitExecutionError> {
-        for component in &self.components {
-            component
-                .execute(memory)
-                .map_err(|_| CircuitExecutionError::ComponentExecutionError)?;
+        self.execute_components::<M, NoopObserver>(&mut scratch)?;
+
+        for (&port, output) in self.outputs.iter().zip(&self.output_wires) {
+            let internal_index = self
+                .memory_map
+                .get(output)
+                .ok_or(CircuitExecutionError::UndefinedOutput(*output))?;
+            memory.write(port, scratch.read(*internal_index)?)?;
         }
 
         Ok(())
@@ -232,7 +646,7 @@ where
 
 impl<T, U> GenericCircuit<T, U>
 where
-    T: Component + Executable<U, CircuitMemory<U>>,
+    T: Component,
     U: Copy,
 {
     /// Creates a new generic circuit.
@@ -242,62 +656,72 @@ where
         inputs: Vec<usize>,
         outputs: Vec<usize>,
     ) -> Self {
-        Self {
+        let mut circuit = Self {
             components,
             memory_map,
+            input_wires: inputs.clone(),
+            output_wires: outputs.clone(),
             inputs,
             outputs,
+            releases: Vec::new(),
+            wire_layout: None,
+            memory_size: 0,
+            levels: Vec::new(),
             _phantom: PhantomData,
-        }
-    }
+        };
+        circuit.update_structure();
 
-    /// Returns the memory size
-    pub fn memory_size(&self) -> usize {
-        self.memory_map
-            .values()
-            .max()
-            .map_or(0, |&max_index| max_index + 1)
+        circuit
     }
-}
 
-/// Executor for a generic circuit.
-#[derive(Debug, PartialEq, Eq)]
-pub struct GenericCircuitExecutor<T, U> {
-    circuit: GenericCircuit<T, U>,
-    memory: CircuitMemory<U>,
-}
-
-impl<T, U> GenericCircuitExecutor<T, U>
-where
-    T: Component + Executable<U, CircuitMemory<U>>,
-    U: Copy,
-{
-    /// Creates a new generic circuit executor.
-    pub fn new(circuit: GenericCircuit<T, U>) -> Self {
-        let memory_size = circuit.memory_size();
-
-        Self {
-            circuit,
-            memory: CircuitMemory::new(memory_size),
+    /// Reinterprets the circuit over another wire value type, such as bit-sliced lanes.
+    /// The circuit structure does not depend on the values carried by its wires.
+    pub fn with_value_type<V: Copy>(self) -> GenericCircuit<T, V> {
+        GenericCircuit {
+            components: self.components,
+            inputs: self.inputs,
+            outputs: self.outputs,
+            input_wires: self.input_wires,
+            output_wires: self.output_wires,
+            memory_map: self.memory_map,
+            releases: self.releases,
+            wire_layout: self.wire_layout,
+            memory_size: self.memory_size,
+            levels: self.levels,
+            _phantom: PhantomData,
         }
     }
 
-    /// Runs the circuit using the provided input values and returns a map of the output values.
-    pub fn run(
-        &mut self,
+    /// Writes the circuit input values to an empty memory.
+    /// Missing inputs are set to `missing_input` when provided, and are an error otherwise.
+    pub(crate) fn write_inputs<M, E>(
+        &self,
+        memory: &mut M,
         inputs: &HashMap<usize, U>,
-    ) -> Result<HashMap<usize, U>, CircuitExecutionError> {
+        missing_input: Option<U>,
+    ) -> Result<(), CircuitExecutionError<E>>
+    where
+        M: Memory<U, Error = CircuitMemoryError>,
+    {
         // Check if the input values match the circuit inputs
-        if inputs.len() != self.circuit.inputs().len() {
+        let expected = match missing_input {
+            Some(_) => self
+                .input_wires
+                .iter()
+                .filter(|input| inputs.contains_key(input))
+                .count(),
+            None => self.input_wires.len(),
+        };
+        if inputs.len() != expected {
             return Err(CircuitExecutionError::InputLengthMismatch);
         }
 
         // Set inputs in  memory
-        for &input_index in self.circuit.inputs() {
-            if let Some(&value) = inputs.get(&input_index) {
+        for &input_index in &self.input_wires {
+            if let Some(value) = inputs.get(&input_index).copied().or(missing_input) {
                 // Translate external input index to internal memory index using the memory_map
-                if let Some(&internal_index) = self.circuit.memory_map.get(&input_index) {
-                    self.memory
+                if let Some(&internal_index) = self.memory_map.get(&input_index) {
+                    memory
                         .write(internal_index, value)
                         .map_err(CircuitExecutionError::MemoryError)?;
                 } else {
@@ -308,14 +732,19 @@ where
             }
         }
 
-        // Execute the circuit
-        self.circuit.execute(&mut self.memory)?;
+        Ok(())
+    }
 
+    /// Reads the circuit output values from an executed memory.
+    pub(crate) fn read_outputs<M, E>(&self, memory: &M) -> RunResult<U, E>
+    where
+        M: Memory<U, Error = CircuitMemoryError>,
+    {
         // Retrieve and return output values
         let mut output_values = HashMap::new();
-        for &output_index in self.circuit.outputs() {
-            if let Some(&internal_index) = self.circuit.memory_map.get(&output_index) {
-                match self.memory.read(internal_index) {
+        for &output_index in &self.output_wires {
+            if let Some(&internal_index) = self.memory_map.get(&output_index) {
+                match memory.read(internal_index) {
                     Ok(value) => {
                         output_values.insert(output_index, value);
                     }
@@ -330,6 +759,513 @@ where
 
         Ok(output_values)
     }
+
+    /// Executes the components in order over a memory laid out by the circuit `memory_map`,
+    /// notifying the observation around each component.
+    /// Reused memory slots are released once their last consumer has executed.
+    pub(crate) fn execute_components<'a, M, O>(
+        &self,
+        memory: &mut O::Memory,
+    ) -> Result<(), CircuitExecutionError<<T as Executable<U, O::Memory>>::Error>>
+    where
+        M: 'a,
+        O: Observation<'a, T, U, M>,
+        T: Executable<U, O::Memory>,
+    {
+        for (index, component) in self.components.iter().enumerate() {
+            O::before_component(memory, index, component);
+            self.execute_component(index, memory)?;
+            O::after_component(memory, index, component);
+            for &slot in self.releases.get(index).into_iter().flatten() {
+                O::release(memory, slot);
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Executes a single component, reporting failures with the original wire ids.
+    pub(crate) fn execute_component<M>(
+        &self,
+        index: usize,
+        memory: &mut M,
+    ) -> Result<(), CircuitExecutionError<<T as Executable<U, M>>::Error>>
+    where
+        T: Executable<U, M>,
+        M: Memory<U>,
+    {
+        let component = &self.components[index];
+        component.execute(memory).map_err(|source| {
+            let (inputs, outputs) = self.component_wires().swap_remove(index);
+            let originals = component
+                .inputs()
+                .iter()
+                .zip(&inputs)
+                .chain(component.outputs().iter().zip(&outputs))
+                .map(|(&slot, &wire)| (slot, wire))
+                .collect::<HashMap<_, _>>();
+            let original = |slot: usize| originals.get(&slot).copied().unwrap_or(slot);
+
+            CircuitExecutionError::ComponentExecutionError {
+                index,
+                inputs,
+                outputs,
+                source: T::map_error_indices(source, &original),
+            }
+        })
+    }
+
+    /// Returns the components in execution order, with reindexed wires.
+    pub fn components(&self) -> &[T] {
+        &self.components
+    }
+
+    /// Returns the components for in-place rewriting by optimisation passes.
+    pub(crate) fn components_mut(&mut self) -> &mut Vec<T> {
+        &mut self.components
+    }
+
+    /// Returns the memory map for in-place rewriting by optimisation passes.
+    pub(crate) fn memory_map_mut(&mut self) -> &mut HashMap<usize, usize> {
+        &mut self.memory_map
+    }
+
+    /// Removes circuit inputs, along with the parent wires they are mounted on.
+    pub(crate) fn remove_inputs(&mut self, wires: &HashSet<usize>) {
+        let keep = self
+            .input_wires
+            .iter()
+            .map(|wire| !wires.contains(wire))
+            .collect::<Vec<_>>();
+
+        let mut flags = keep.iter();
+        self.input_wires.retain(|_| *flags.next().unwrap_or(&true));
+        if self.inputs.len() == keep.len() {
+            let mut flags = keep.iter();
+            self.inputs.retain(|_| *flags.next().unwrap_or(&true));
+        }
+    }
+
+    /// Returns the circuit own input wire ids, regardless of the wires it is mounted on.
+    pub fn input_wires(&self) -> &[usize] {
+        &self.input_wires
+    }
+
+    /// Returns the circuit own output wire ids, regardless of the wires it is mounted on.
+    pub fn output_wires(&self) -> &[usize] {
+        &self.output_wires
+    }
+
+    /// Returns the map from original wire ids to memory indices.
+    pub fn memory_map(&self) -> &HashMap<usize, usize> {
+        &self.memory_map
+    }
+
+    /// Returns the memory size
+    pub fn memory_size(&self) -> usize {
+        self.memory_size
+    }
+
+    /// Recomputes the memory size and the component levels, once optimisation passes have
+    /// rewritten the circuit.
+    pub(crate) fn update_structure(&mut self) {
+        let component_wires = self
+            .components
+            .iter()
+            .flat_map(|component| component.inputs().iter().chain(component.outputs()));
+
+        self.memory_size = self
+            .memory_map
+            .values()
+            .chain(component_wires)
+            .max()
+            .map_or(0, |&max_index| max_index + 1);
+
+        let mut wire_levels = HashMap::new();
+        self.levels = self
+            .components
+            .iter()
+            .map(|component| {
+                let level = component
+                    .inputs()
+                    .iter()
+                    .filter_map(|input| wire_levels.get(input))
+                    .map(|&level| level + 1)
+                    .max()
+                    .unwrap_or(0);
+                for &output in component.outputs() {
+                    wire_levels.insert(output, level);
+                }
+                level
+            })
+            .collect();
+    }
+
+    /// Returns the memory slots released after each component, in execution order.
+    /// Empty unless memory slots are reused.
+    pub fn releases(&self) -> &[Vec<usize>] {
+        &self.releases
+    }
+
+    /// Sets the memory slots released after each component, for the memory reuse pass, along
+    /// with the layout the circuit had before its slots were reused.
+    pub(crate) fn set_releases(&mut self, releases: Vec<Vec<usize>>, layout: WireLayout) {
+        self.releases = releases;
+        self.wire_layout = Some(layout);
+    }
+
+    /// Drops the memory slot releases, returning the layout the circuit had before its slots
+    /// were reused, if they were.
+    pub(crate) fn take_releases(&mut self) -> Option<WireLayout> {
+        self.releases = Vec::new();
+        self.wire_layout.take()
+    }
+
+    /// Returns the map from every original wire id to its memory slot, as if slots were not
+    /// reused. Unlike `memory_map`, it keeps the internal wires of reused circuits.
+    pub(crate) fn wire_map(&self) -> &HashMap<usize, usize> {
+        self.wire_layout
+            .as_ref()
+            .map_or(&self.memory_map, |layout| &layout.memory_map)
+    }
+
+    /// Returns the original wire ids read and written by each component, in execution order.
+    /// Wires sharing a memory slot, such as merged wires, are reported by the lowest id.
+    pub(crate) fn component_wires(&self) -> Vec<(Vec<usize>, Vec<usize>)> {
+        let originals = self.original_wires();
+        let original = |slots: &[usize]| {
+            slots
+                .iter()
+                .map(|slot| originals.get(slot).copied().unwrap_or(*slot))
+                .collect()
+        };
+
+        match &self.wire_layout {
+            Some(layout) => layout
+                .slots
+                .iter()
+                .map(|(inputs, outputs)| (original(inputs), original(outputs)))
+                .collect(),
+            None => self
+                .components
+                .iter()
+                .map(|component| (original(component.inputs()), original(component.outputs())))
+                .collect(),
+        }
+    }
+
+    /// Returns the topological level of each component, in execution order.
+    /// Components only reading circuit inputs are at level 0, and every other component is
+    /// one level above the deepest component producing one of its inputs.
+    pub fn levels(&self) -> &[usize] {
+        &self.levels
+    }
+
+    /// Maps the memory slots of `wire_map` back to the original wire ids.
+    /// Slots shared by several wires map to the lowest one.
+    fn original_wires(&self) -> HashMap<usize, usize> {
+        let wire_map = self.wire_map();
+        let mut originals = HashMap::with_capacity(wire_map.len());
+        for (&original, &internal) in wire_map {
+            originals
+                .entry(internal)
+                .and_modify(|lowest: &mut usize| *lowest = (*lowest).min(original))
+                .or_insert(original);
+        }
+
+        originals
+    }
+}
+
+/// Serialized fields of a circuit, checked for consistency before the circuit is loaded.
+#[cfg(feature = "serde")]
+#[derive(serde::Deserialize)]
+struct CircuitData<T, U> {
+    components: Vec<T>,
+    inputs: Vec<usize>,
+    outputs: Vec<usize>,
+    input_wires: Vec<usize>,
+    output_wires: Vec<usize>,
+    memory_map: HashMap<usize, usize>,
+    releases: Vec<Vec<usize>>,
+    wire_layout: Option<WireLayout>,
+    memory_size: usize,
+    _phantom: PhantomData<U>,
+}
+
+#[cfg(feature = "serde")]
+impl<T, U> TryFrom<CircuitData<T, U>> for GenericCircuit<T, U>
+where
+    T: Component,
+    U: Copy,
+{
+    type Error = DataError;
+
+    fn try_from(data: CircuitData<T, U>) -> Result<Self, Self::Error> {
+        let mut circuit = Self {
+            components: data.components,
+            inputs: data.inputs,
+            outputs: data.outputs,
+            input_wires: data.input_wires,
+            output_wires: data.output_wires,
+            memory_map: data.memory_map,
+            releases: data.releases,
+            wire_layout: data.wire_layout,
+            memory_size: 0,
+            levels: Vec::new(),
+            _phantom: PhantomData,
+        };
+        circuit.update_structure();
+
+        if circuit.memory_size != data.memory_size {
+            return Err(DataError::MemorySizeMismatch {
+                expected: circuit.memory_size,
+                found: data.memory_size,
+            });
+        }
+        if let Some(&slot) = circuit
+            .releases
+            .iter()
+            .flatten()
+            .find(|&&slot| slot >= circuit.memory_size)
+        {
+            return Err(DataError::SlotOutOfBounds(slot));
+        }
+
+        // Released slots and the wire layout are recorded together, for every component
+        let reused = !circuit.releases.is_empty();
+        let layout_matches = circuit.wire_layout.as_ref().is_none_or(|layout| {
+            layout.slots.len() == circuit.components.len()
+                && circuit.components.iter().zip(&layout.slots).all(
+                    |(component, (inputs, outputs))| {
+                        component.inputs().len() == inputs.len()
+                            && component.outputs().len() == outputs.len()
+                    },
+                )
+        });
+        if (reused && circuit.releases.len() != circuit.components.len())
+            || reused != circuit.wire_layout.is_some()
+            || !layout_matches
+        {
+            return Err(DataError::ReleasesMismatch);
+        }
+
+        Ok(circuit)
+    }
+}
+
+/// Result of a single circuit run: the output values, or the execution error.
+pub type RunResult<U, E> = Result<HashMap<usize, U>, CircuitExecutionError<E>>;
+
+/// Executor for a generic circuit, generic over the memory used to execute it.
+#[derive(Debug, PartialEq, Eq)]
+pub struct GenericCircuitExecutor<T, U, M = CircuitMemory<U>> {
+    circuit: GenericCircuit<T, U>,
+    memory: M,
+    missing_input: Option<U>,
+}
+
+impl<T, U> GenericCircuitExecutor<T, U>
+where
+    T: Component + Executable<U, CircuitMemory<U>>,
+    U: Copy,
+{
+    /// Creates a new generic circuit executor over a `CircuitMemory`.
+    pub fn new(circuit: GenericCircuit<T, U>) -> Self {
+        Self::with_memory(circuit)
+    }
+}
+
+impl<T, U, M> GenericCircuitExecutor<T, U, M>
+where
+    T: Component + Executable<U, M>,
+    U: Copy,
+    M: SizedMemory<U, Error = CircuitMemoryError>,
+{
+    /// Creates a new generic circuit executor over the memory type `M`.
+    pub fn with_memory(circuit: GenericCircuit<T, U>) -> Self {
+        let memory_size = circuit.memory_size();
+
+        Self {
+            circuit,
+            memory: M::with_size(memory_size),
+            missing_input: None,
+        }
+    }
+
+    /// Sets the value of the circuit inputs missing from the provided input values, such as
+    /// an unknown value. By default, missing inputs are an error.
+    pub fn set_missing_input(&mut self, value: Option<U>) -> &mut Self {
+        self.missing_input = value;
+        self
+    }
+
+    /// Returns the executed circuit.
+    pub fn circuit(&self) -> &GenericCircuit<T, U> {
+        &self.circuit
+    }
+
+    /// Returns the value of a wire after the last run, by original wire id.
+    /// Inputs whose memory slot is released have no value, as the slot may hold other wires.
+    pub fn read_wire(&self, wire: usize) -> Option<U> {
+        let slot = *self.circuit.memory_map.get(&wire)?;
+        let released = || {
+            self.circuit
+                .releases
+                .iter()
+                .flatten()
+                .any(|&other| other == slot)
+        };
+        if self.circuit.input_wires.contains(&wire) && released() {
+            return None;
+        }
+        self.memory.read(slot).ok()
+    }
+
+    /// Clears the executor memory so the circuit can be executed again.
+    pub fn reset(&mut self) {
+        self.memory.clear();
+    }
+
+    /// Runs the circuit using the provided input values and returns a map of the output values.
+    /// The memory is reset before each run, so the executor can be reused.
+    pub fn run(
+        &mut self,
+        inputs: &HashMap<usize, U>,
+    ) -> Result<HashMap<usize, U>, CircuitExecutionError<T::Error>> {
+        self.run_observed(inputs, NoopObserver)
+    }
+
+    /// Writes the input values to an empty memory and returns a debugger stopped before the
+    /// first component.
+    pub fn debug(
+        &mut self,
+        inputs: &HashMap<usize, U>,
+    ) -> Result<Debugger<'_, T, U, M>, CircuitExecutionError<T::Error>> {
+        self.reset();
+        self.circuit
+            .write_inputs(&mut self.memory, inputs, self.missing_input)?;
+
+        Ok(Debugger::new(&self.circuit, &mut self.memory))
+    }
+
+    /// Runs the circuit once for every set of input values, reusing the same memory allocation.
+    /// Stops at the first failing run.
+    pub fn run_many(
+        &mut self,
+        inputs: &[HashMap<usize, U>],
+    ) -> Result<Vec<HashMap<usize, U>>, CircuitExecutionError<T::Error>> {
+        inputs.iter().map(|input| self.run(input)).collect()
+    }
+
+    /// Runs the circuit once for every set of input values and returns the result of each run,
+    /// in input order. A failing run does not stop the others.
+    pub fn run_batch(&mut self, inputs: &[HashMap<usize, U>]) -> Vec<RunResult<U, T::Error>> {
+        inputs.iter().map(|input| self.run(input)).collect()
+    }
+
+    /// Same as `run_batch`, splitting the input sets across `workers` threads.
+    /// Every worker executes its share of the batch over its own memory.
+    pub fn run_batch_parallel(
+        &self,
+        inputs: &[HashMap<usize, U>],
+        workers: usize,
+    ) -> Vec<RunResult<U, T::Error>>
+    where
+        T: Sync,
+        T::Error: Send,
+        U: Send + Sync,
+    {
+        if inputs.is_empty() {
+            return Vec::new();
+        }
+        let chunk_size = inputs.len().div_ceil(workers.max(1));
+        let circuit = &self.circuit;
+        let missing_input = self.missing_input;
+
+        thread::scope(|scope| {
+            let handles = inputs
+                .chunks(chunk_size)
+                .map(|chunk| {
+                    scope.spawn(|| {
+                        let mut memory = M::with_size(circuit.memory_size());
+                        chunk
+                            .iter()
+                            .map(|input| {
+                                memory.clear();
+                                Self::run_on(
+                                    circuit,
+                                    &mut memory,
+                                    input,
+                                    missing_input,
+                                    NoopObserver,
+                                )
+                            })
+                            .collect::<Vec<_>>()
+                    })
+                })
+                .collect::<Vec<_>>();
+
+            handles
+                .into_iter()
+                .flat_map(|handle| {
+                    handle
+                        .join()
+                        .unwrap_or_else(|error| panic::resume_unwind(error))
+                })
+                .collect()
+        })
+    }
+}
+
+impl<T, U, M> GenericCircuitExecutor<T, U, M>
+where
+    T: Component,
+    U: Copy,
+    M: SizedMemory<U, Error = CircuitMemoryError>,
+{
+    /// Runs the circuit like `run`, notifying the observer of every component execution and
+    /// memory access. Pass a mutable reference to any `ExecutionObserver`, or `NoopObserver`
+    /// for a plain run. Output values are read without notifying the observer.
+    pub fn run_observed<'a, O, E>(
+        &'a mut self,
+        inputs: &HashMap<usize, U>,
+        observer: O,
+    ) -> RunResult<U, E>
+    where
+        O: Observation<'a, T, U, M>,
+        O::Memory: Memory<U, Error = CircuitMemoryError>,
+        T: Executable<U, O::Memory, Error = E>,
+    {
+        self.memory.clear();
+        Self::run_on(
+            &self.circuit,
+            &mut self.memory,
+            inputs,
+            self.missing_input,
+            observer,
+        )
+    }
+
+    /// Runs the circuit over an empty memory.
+    fn run_on<'a, O, E>(
+        circuit: &GenericCircuit<T, U>,
+        memory: &'a mut M,
+        inputs: &HashMap<usize, U>,
+        missing_input: Option<U>,
+        observer: O,
+    ) -> RunResult<U, E>
+    where
+        O: Observation<'a, T, U, M>,
+        O::Memory: Memory<U, Error = CircuitMemoryError>,
+        T: Executable<U, O::Memory, Error = E>,
+    {
+        observer.observe(memory, |memory| {
+            circuit.write_inputs(memory, inputs, missing_input)?;
+            circuit.execute_components::<M, O>(memory)?;
+            circuit.read_outputs(O::memory(memory))
+        })
+    }
 }
 
 #[derive(Debug, Error, PartialEq, Eq)]
@@ -358,16 +1294,35 @@ pub enum CircuitBuilderError {
     OutputIsACircuitInput(usize),
     #[error("Unused inputs: {0:?}")]
     UnusedInputs(Vec<usize>),
+    #[error("Output {0} is not driven by any component or circuit input")]
+    UndrivenOutput(usize),
+    #[error("Inputs {0:?} are never produced by any component")]
+    DanglingInputs(Vec<usize>),
+    #[error("Components form a cycle through wires {0:?}")]
+    CycleError(Vec<usize>),
 }
 
 #[derive(Debug, Error, PartialEq, Eq)]
-pub enum CircuitExecutionError {
-    #[error("Component execution error")]
-    ComponentExecutionError,
+pub enum CircuitExecutionError<E> {
+    #[error(
+        "Component {index} execution error (inputs {inputs:?}, outputs {outputs:?}): {source:?}"
+    )]
+    ComponentExecutionError {
+        /// Position of the failing component in the circuit.
+        index: usize,
+        /// Original input wire ids of the failing component.
+        inputs: Vec<usize>,
+        /// Original output wire ids of the failing component.
+        outputs: Vec<usize>,
+        /// Error returned by the component.
+        source: E,
+    },
     #[error("Input {0} not defined")]
     InputNotFoundError(usize),
     #[error("Input length mismatch")]
     InputLengthMismatch,
+    #[error("Output length mismatch")]
+    OutputLengthMismatch,
     #[error("Input {0} not found in memory as a circuit input")]
     MemoryMappingError(usize),
     #[error("Circuit memory error: {0}")]
@@ -381,6 +1336,7 @@ mod tests {
     use super::*;
     use std::fmt::Debug;
 
+    #[allow(clippy::upper_case_acronyms)]
     #[derive(PartialEq, Eq, Clone)]
     pub enum BinaryOperation {
         AND,
@@ -440,6 +1396,43 @@ mod tests {
         }
     }
 
+    /// Gate propagating memory errors instead of panicking.
+    #[derive(Debug, PartialEq, Eq, Clone)]
+    pub struct CheckedGate(BinaryGate);
+
+    impl Component for CheckedGate {
+        fn inputs(&self) -> &[usize] {
+            self.0.inputs()
+        }
+
+        fn outputs(&self) -> &[usize] {
+            self.0.outputs()
+        }
+
+        fn set_inputs(&mut self, inputs: Vec<usize>) {
+            self.0.set_inputs(inputs);
+        }
+
+        fn set_outputs(&mut self, outputs: Vec<usize>) {
+            self.0.set_outputs(outputs);
+        }
+    }
+
+    impl Executable<bool, CircuitMemory<bool>> for CheckedGate {
+        type Error = CircuitMemoryError;
+
+        fn execute(&self, memory: &mut CircuitMemory<bool>) -> Result<(), Self::Error> {
+            let a = memory.read(self.0.inputs[0])?;
+            let b = memory.read(self.0.inputs[1])?;
+
+            let result = match self.0.op {
+                BinaryOperation::AND => a && b,
+                BinaryOperation::XOR => a ^ b,
+            };
+            memory.write(self.0.outputs[0], result)
+        }
+    }
+
     #[test]
     fn test_memory_operations() {
         let mut memory: CircuitMemory<bool> = CircuitMemory::new(10);
@@ -536,6 +1529,7 @@ mod tests {
         assert_eq!(circuit.memory_map.get(&510), Some(&4));
 
         // Test gates inputs and outputs reindexing
+        #[allow(clippy::get_first)]
         let first_gate = circuit.components.get(0).unwrap();
         assert_eq!(first_gate.inputs(), &[0, 1]);
         assert_eq!(first_gate.outputs(), &[2]);
@@ -623,21 +1617,28 @@ mod tests {
 
     #[test]
     fn test_builder_unused_input() {
-        let mut builder = CircuitBuilder::<BinaryGate, bool>::new();
-        builder.add_inputs(&[3, 4]);
+        let builder = |outputs: &[usize]| {
+            let mut builder = CircuitBuilder::<BinaryGate, bool>::new();
+            builder.add_inputs(&[3, 4]).add_outputs(outputs);
+
+            let gate1 = BinaryGate {
+                op: BinaryOperation::AND,
+                inputs: vec![3],
+                outputs: vec![7],
+            };
 
-        let gate1 = BinaryGate {
-            op: BinaryOperation::AND,
-            inputs: vec![3],
-            outputs: vec![7],
+            builder.add_component(gate1).unwrap();
+            builder
         };
 
-        builder.add_component(gate1).unwrap();
-
         assert_eq!(
-            builder.build(),
+            builder(&[]).build(),
             Err(CircuitBuilderError::UnusedInputs(vec![4]))
         );
+
+        // Inputs declared as outputs are used
+        let circuit = builder(&[4, 7]).build().unwrap();
+        assert_eq!(circuit.output_wires(), &[4, 7]);
     }
 
     #[test]
@@ -667,4 +1668,336 @@ mod tests {
         let output = executor.run(&input_values).unwrap();
         assert_eq!(output.get(&4), Some(&true));
     }
+
+    #[test]
+    fn test_memory_clear() {
+        let mut memory: CircuitMemory<bool> = CircuitMemory::new(2);
+        memory.write(0, true).unwrap();
+
+        memory.clear();
+
+        assert_eq!(
+            memory.read(0),
+            Err(CircuitMemoryError::UninitializedSlot(0))
+        );
+        assert_eq!(memory.write(0, false), Ok(()));
+    }
+
+    #[test]
+    fn test_executor_reuse() {
+        let mut builder = CircuitBuilder::<BinaryGate, bool>::new();
+        builder.add_inputs(&[0, 1, 3]);
+
+        let and_gate = BinaryGate {
+            op: BinaryOperation::AND,
+            inputs: vec![0, 1],
+            outputs: vec![2],
+        };
+        let xor_gate = BinaryGate {
+            op: BinaryOperation::XOR,
+            inputs: vec![2, 3],
+            outputs: vec![4],
+        };
+
+        builder.add_component(and_gate).unwrap();
+        builder.add_component(xor_gate).unwrap();
+
+        let mut executor = GenericCircuitExecutor::new(builder.build().unwrap());
+
+        let first = executor
+            .run(&HashMap::from([(0, true), (1, true), (3, false)]))
+            .unwrap();
+        assert_eq!(first.get(&4), Some(&true));
+
+        let second = executor
+            .run(&HashMap::from([(0, true), (1, true), (3, true)]))
+            .unwrap();
+        assert_eq!(second.get(&4), Some(&false));
+
+        let outputs = executor
+            .run_many(&[
+                HashMap::from([(0, false), (1, true), (3, false)]),
+                HashMap::from([(0, false), (1, true), (3, true)]),
+            ])
+            .unwrap();
+        assert_eq!(outputs[0].get(&4), Some(&false));
+        assert_eq!(outputs[1].get(&4), Some(&true));
+    }
+
+    #[test]
+    fn test_run_batch() {
+        let mut builder = CircuitBuilder::<BinaryGate, bool>::new();
+        builder.add_inputs(&[0, 1]);
+        builder
+            .add_component(BinaryGate {
+                op: BinaryOperation::XOR,
+                inputs: vec![0, 1],
+                outputs: vec![2],
+            })
+            .unwrap();
+        let mut executor = GenericCircuitExecutor::new(builder.build().unwrap());
+
+        let mut inputs = (0..10)
+            .map(|i| HashMap::from([(0, i % 2 == 0), (1, i % 3 == 0)]))
+            .collect::<Vec<_>>();
+        inputs[4] = HashMap::from([(0, true)]);
+        let expected = (0..10)
+            .map(|i| match i {
+                4 => Err(CircuitExecutionError::InputLengthMismatch),
+                _ => Ok(HashMap::from([(2, (i % 2 == 0) ^ (i % 3 == 0))])),
+            })
+            .collect::<Vec<_>>();
+
+        assert_eq!(executor.run_batch(&inputs), expected);
+        for workers in [0, 1, 3, 16] {
+            assert_eq!(executor.run_batch_parallel(&inputs, workers), expected);
+        }
+        assert!(executor.run_batch_parallel(&[], 4).is_empty());
+    }
+
+    #[test]
+    fn test_component_execution_error() {
+        let gate = CheckedGate(BinaryGate {
+            op: BinaryOperation::AND,
+            inputs: vec![0, 1],
+            outputs: vec![2],
+        });
+        let circuit = GenericCircuit::<CheckedGate, bool>::new(
+            vec![gate],
+            HashMap::from([(10, 0), (11, 1), (12, 2)]),
+            vec![10],
+            vec![12],
+        );
+        let mut executor = GenericCircuitExecutor::new(circuit);
+
+        let error = executor.run(&HashMap::from([(10, true)])).unwrap_err();
+
+        assert_eq!(
+            error,
+            CircuitExecutionError::ComponentExecutionError {
+                index: 0,
+                inputs: vec![10, 11],
+                outputs: vec![12],
+                source: CircuitMemoryError::UninitializedSlot(1),
+            }
+        );
+        assert!(std::error::Error::source(&error).is_some());
+    }
+
+    #[test]
+    fn test_component_execution_error_shared_slot() {
+        let gate = CheckedGate(BinaryGate {
+            op: BinaryOperation::XOR,
+            inputs: vec![0, 1],
+            outputs: vec![2],
+        });
+        // Wires 11 and 13 share slot 1, as after memory reuse
+        let circuit = GenericCircuit::<CheckedGate, bool>::new(
+            vec![gate],
+            HashMap::from([(10, 0), (13, 1), (11, 1), (12, 2)]),
+            vec![10],
+            vec![12],
+        );
+        let mut executor = GenericCircuitExecutor::new(circuit);
+
+        assert_eq!(
+            executor.run(&HashMap::from([(10, true)])),
+            Err(CircuitExecutionError::ComponentExecutionError {
+                index: 0,
+                inputs: vec![10, 11],
+                outputs: vec![12],
+                source: CircuitMemoryError::UninitializedSlot(1),
+            })
+        );
+    }
+
+    #[test]
+    fn test_deferred_builder_sorts_components() {
+        let mut builder = CircuitBuilder::<BinaryGate, bool>::new_deferred();
+        builder.add_inputs(&[0, 1, 3]);
+
+        let xor_gate = BinaryGate {
+            op: BinaryOperation::XOR,
+            inputs: vec![2, 3],
+            outputs: vec![4],
+        };
+        let and_gate = BinaryGate {
+            op: BinaryOperation::AND,
+            inputs: vec![0, 1],
+            outputs: vec![2],
+        };
+
+        builder.add_component(xor_gate).unwrap();
+        builder.add_component(and_gate).unwrap();
+
+        let circuit = builder.build().unwrap();
+        assert_eq!(circuit.components[0].op, BinaryOperation::AND);
+        assert_eq!(circuit.components[1].op, BinaryOperation::XOR);
+
+        let mut executor = GenericCircuitExecutor::new(circuit);
+        let output = executor
+            .run(&HashMap::from([(0, true), (1, false), (3, true)]))
+            .unwrap();
+        assert_eq!(output.get(&4), Some(&true));
+    }
+
+    #[test]
+    fn test_deferred_builder_cycle() {
+        let mut builder = CircuitBuilder::<BinaryGate, bool>::new_deferred();
+        builder.add_inputs(&[0]);
+
+        builder
+            .add_component(BinaryGate {
+                op: BinaryOperation::AND,
+                inputs: vec![0, 3],
+                outputs: vec![1],
+            })
+            .unwrap();
+        builder
+            .add_component(BinaryGate {
+                op: BinaryOperation::XOR,
+                inputs: vec![1, 0],
+                outputs: vec![2],
+            })
+            .unwrap();
+        builder
+            .add_component(BinaryGate {
+                op: BinaryOperation::XOR,
+                inputs: vec![2, 0],
+                outputs: vec![3],
+            })
+            .unwrap();
+
+        assert_eq!(
+            builder.build(),
+            Err(CircuitBuilderError::CycleError(vec![1, 2, 3]))
+        );
+    }
+
+    #[test]
+    fn test_deferred_builder_dangling_inputs() {
+        let mut builder = CircuitBuilder::<BinaryGate, bool>::new_deferred();
+        builder.add_inputs(&[0]);
+
+        builder
+            .add_component(BinaryGate {
+                op: BinaryOperation::AND,
+                inputs: vec![0, 7],
+                outputs: vec![1],
+            })
+            .unwrap();
+        builder
+            .add_component(BinaryGate {
+                op: BinaryOperation::XOR,
+                inputs: vec![1, 5],
+                outputs: vec![2],
+            })
+            .unwrap();
+
+        assert_eq!(
+            builder.build(),
+            Err(CircuitBuilderError::DanglingInputs(vec![5, 7]))
+        );
+    }
+
+    #[test]
+    fn test_builder_declared_outputs() {
+        let mut builder = CircuitBuilder::<BinaryGate, bool>::new();
+        builder.add_inputs(&[0, 1, 3]).add_outputs(&[4, 2]);
+
+        let and_gate = BinaryGate {
+            op: BinaryOperation::AND,
+            inputs: vec![0, 1],
+            outputs: vec![2],
+        };
+        let xor_gate = BinaryGate {
+            op: BinaryOperation::XOR,
+            inputs: vec![2, 3],
+            outputs: vec![4],
+        };
+
+        builder.add_component(and_gate).unwrap();
+        builder.add_component(xor_gate).unwrap();
+
+        let circuit = builder.build().unwrap();
+        assert_eq!(circuit.outputs(), &[4, 2]);
+
+        let mut executor = GenericCircuitExecutor::new(circuit);
+        let output = executor
+            .run(&HashMap::from([(0, true), (1, true), (3, true)]))
+            .unwrap();
+        assert_eq!(output, HashMap::from([(2, true), (4, false)]));
+    }
+
+    #[test]
+    fn test_builder_derived_outputs_order() {
+        let mut builder = CircuitBuilder::<BinaryGate, bool>::new();
+        builder.add_inputs(&[0, 1]);
+
+        for output in [9, 3, 7, 5] {
+            builder
+                .add_component(BinaryGate {
+                    op: BinaryOperation::AND,
+                    inputs: vec![0, 1],
+                    outputs: vec![output],
+                })
+                .unwrap();
+        }
+
+        let circuit = builder.build().unwrap();
+        assert_eq!(circuit.outputs(), &[9, 3, 7, 5]);
+    }
+
+    #[test]
+    fn test_builder_undriven_output() {
+        let mut builder = CircuitBuilder::<BinaryGate, bool>::new();
+        builder.add_inputs(&[0, 1]).add_outputs(&[2, 6]);
+
+        builder
+            .add_component(BinaryGate {
+                op: BinaryOperation::AND,
+                inputs: vec![0, 1],
+                outputs: vec![2],
+            })
+            .unwrap();
+
+        assert_eq!(builder.build(), Err(CircuitBuilderError::UndrivenOutput(6)));
+    }
+
+    #[test]
+    fn test_nested_circuit_execution() {
+        let mut builder = CircuitBuilder::<BinaryGate, bool>::new();
+        builder.add_inputs(&[0, 1]);
+        builder
+            .add_component(BinaryGate {
+                op: BinaryOperation::AND,
+                inputs: vec![0, 1],
+                outputs: vec![2],
+            })
+            .unwrap();
+        let mut and_circuit = builder.build().unwrap();
+
+        // Mount the circuit on wires 10 and 11 of a parent memory
+        let mut memory = CircuitMemory::new(13);
+        memory.write(10, true).unwrap();
+        memory.write(11, true).unwrap();
+
+        and_circuit.set_inputs(vec![10]);
+        assert_eq!(
+            and_circuit.execute(&mut memory),
+            Err(CircuitExecutionError::InputLengthMismatch)
+        );
+
+        and_circuit.set_inputs(vec![10, 11]);
+        and_circuit.set_outputs(vec![12]);
+        assert_eq!(and_circuit.execute(&mut memory), Ok(()));
+        assert_eq!(memory.read(12), Ok(true));
+
+        // Every execution starts from an empty scratch memory
+        let mut memory = CircuitMemory::new(13);
+        memory.write(10, true).unwrap();
+        memory.write(11, false).unwrap();
+        assert_eq!(and_circuit.execute(&mut memory), Ok(()));
+        assert_eq!(memory.read(12), Ok(false));
+    }
 }
@@ -0,0 +1,320 @@
+//! # Debugger Module
+//!
+//! Contains the step-through debugger for circuit executions.
+
+use crate::{
+    circuit::{CircuitExecutionError, CircuitMemoryError, GenericCircuit, RunResult},
+    model::{Component, Executable, SizedMemory},
+};
+use std::collections::{BTreeSet, HashMap, HashSet};
+
+/// Execution breakpoint.
+/// Breakpoints are ordered by kind, then by position or wire id.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Breakpoint {
+    /// Stops before the component at this position in execution order.
+    Component(usize),
+    /// Stops after the component writing this original wire id.
+    Wire(usize),
+}
+
+/// Reason why the debugger stopped.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StopReason {
+    /// A breakpoint was hit.
+    Breakpoint(Breakpoint),
+    /// The requested component or wire was reached.
+    Reached,
+    /// Every component has executed.
+    Finished,
+}
+
+/// Step-through debugger over a single circuit execution, created by
+/// `GenericCircuitExecutor::debug` once the input values are written.
+/// Components execute one at a time, in execution order.
+pub struct Debugger<'a, T, U, M> {
+    circuit: &'a GenericCircuit<T, U>,
+    memory: &'a mut M,
+    next: usize,
+    breakpoints: BTreeSet<Breakpoint>,
+    released: HashSet<usize>,
+}
+
+impl<'a, T, U, M> Debugger<'a, T, U, M>
+where
+    T: Component + Executable<U, M>,
+    U: Copy,
+    M: SizedMemory<U, Error = CircuitMemoryError>,
+{
+    /// Creates a debugger stopped before the first component.
+    pub(crate) fn new(circuit: &'a GenericCircuit<T, U>, memory: &'a mut M) -> Self {
+        Self {
+            circuit,
+            memory,
+            next: 0,
+            breakpoints: BTreeSet::new(),
+            released: HashSet::new(),
+        }
+    }
+
+    /// Returns the debugged circuit.
+    pub fn circuit(&self) -> &GenericCircuit<T, U> {
+        self.circuit
+    }
+
+    /// Returns the position of the next component to execute, or `None` once finished.
+    pub fn next_component(&self) -> Option<usize> {
+        (self.next < self.circuit.components().len()).then_some(self.next)
+    }
+
+    /// Returns whether every component has executed.
+    pub fn is_finished(&self) -> bool {
+        self.next_component().is_none()
+    }
+
+    /// Adds a breakpoint.
+    pub fn set_breakpoint(&mut self, breakpoint: Breakpoint) -> &mut Self {
+        self.breakpoints.insert(breakpoint);
+        self
+    }
+
+    /// Removes a breakpoint, returning whether it was set.
+    pub fn remove_breakpoint(&mut self, breakpoint: Breakpoint) -> bool {
+        self.breakpoints.remove(&breakpoint)
+    }
+
+    /// Removes every breakpoint.
+    pub fn clear_breakpoints(&mut self) {
+        self.breakpoints.clear();
+    }
+
+    /// Returns the current value of a wire by original id, or `None` if it is not written yet.
+    /// Internal wires are not addressable once memory slots are reused, and inputs lose their
+    /// value once their slot is released.
+    pub fn read_wire(&self, wire: usize) -> Option<U> {
+        let slot = *self.circuit.memory_map().get(&wire)?;
+        if self.released.contains(&slot) && self.circuit.input_wires().contains(&wire) {
+            return None;
+        }
+        self.memory.read(slot).ok()
+    }
+
+    /// Returns the current values of every written wire, by original id.
+    pub fn wire_values(&self) -> HashMap<usize, U> {
+        self.circuit
+            .memory_map()
+            .keys()
+            .filter_map(|&wire| Some((wire, self.read_wire(wire)?)))
+            .collect()
+    }
+
+    /// Executes the next component and returns its position, or `None` once finished.
+    /// On error the debugger stays before the failing component.
+    pub fn step(&mut self) -> Result<Option<usize>, CircuitExecutionError<T::Error>> {
+        let Some(index) = self.next_component() else {
+            return Ok(None);
+        };
+
+        self.circuit.execute_component(index, self.memory)?;
+        for &slot in self.circuit.releases().get(index).into_iter().flatten() {
+            self.memory.release(slot);
+            self.released.insert(slot);
+        }
+        self.next += 1;
+
+        Ok(Some(index))
+    }
+
+    /// Executes components until a breakpoint is hit or every component has executed.
+    /// A component breakpoint on the next component is ignored, so execution can resume.
+    /// When a component writes several wires with breakpoints, the lowest wire is reported.
+    pub fn resume(&mut self) -> Result<StopReason, CircuitExecutionError<T::Error>> {
+        self.advance(|_, _| false, |_| false)
+    }
+
+    /// Executes components until the component at `index` is the next to execute, stopping
+    /// earlier on breakpoints.
+    pub fn run_until(
+        &mut self,
+        index: usize,
+    ) -> Result<StopReason, CircuitExecutionError<T::Error>> {
+        self.advance(|_, _| false, |next| next == index)
+    }
+
+    /// Executes components until the component writing the wire, by original id, has
+    /// executed, stopping earlier on breakpoints.
+    pub fn run_until_wire_written(
+        &mut self,
+        wire: usize,
+    ) -> Result<StopReason, CircuitExecutionError<T::Error>> {
+        self.advance(|debugger, index| debugger.writes(index, wire), |_| false)
+    }
+
+    /// Returns the circuit output values once every component has executed.
+    pub fn outputs(&self) -> RunResult<U, T::Error> {
+        self.circuit.read_outputs(self.memory)
+    }
+
+    /// Executes components until `written` holds after a component or `reached` holds for
+    /// the next component.
+    fn advance(
+        &mut self,
+        written: impl Fn(&Self, usize) -> bool,
+        reached: impl Fn(usize) -> bool,
+    ) -> Result<StopReason, CircuitExecutionError<T::Error>> {
+        let mut resumed = true;
+        while let Some(next) = self.next_component() {
+            if reached(next) {
+                return Ok(StopReason::Reached);
+            }
+            let breakpoint = Breakpoint::Component(next);
+            if !resumed && self.breakpoints.contains(&breakpoint) {
+                return Ok(StopReason::Breakpoint(breakpoint));
+            }
+            resumed = false;
+
+            self.step()?;
+            if written(self, next) {
+                return Ok(StopReason::Reached);
+            }
+            if let Some(&breakpoint) = self.breakpoints.iter().find(|breakpoint| {
+                matches!(breakpoint, Breakpoint::Wire(wire) if self.writes(next, *wire))
+            }) {
+                return Ok(StopReason::Breakpoint(breakpoint));
+            }
+        }
+
+        Ok(StopReason::Finished)
+    }
+
+    /// Returns whether the component at `index` writes the wire, by original id.
+    fn writes(&self, index: usize, wire: usize) -> bool {
+        if self.circuit.input_wires().contains(&wire) {
+            return false;
+        }
+        self.circuit
+            .memory_map()
+            .get(&wire)
+            .is_some_and(|slot| self.circuit.components()[index].outputs().contains(slot))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        circuit::{CircuitBuilder, GenericCircuitExecutor},
+        gates::boolean::{self, BooleanGate, BooleanOperation},
+    };
+
+    /// Executor of a full adder with inputs 0, 1, 2, sum 5 and carry 7.
+    fn full_adder() -> GenericCircuitExecutor<BooleanGate, bool> {
+        GenericCircuitExecutor::new(boolean::tests::full_adder())
+    }
+
+    fn inputs() -> HashMap<usize, bool> {
+        HashMap::from([(0, true), (1, false), (2, true)])
+    }
+
+    #[test]
+    fn test_step() {
+        let mut executor = full_adder();
+        let mut debugger = executor.debug(&inputs()).unwrap();
+
+        assert_eq!(debugger.read_wire(0), Some(true));
+        assert_eq!(debugger.read_wire(3), None);
+        assert_eq!(debugger.step(), Ok(Some(0)));
+        assert_eq!(debugger.read_wire(3), Some(true));
+        assert_eq!(debugger.next_component(), Some(1));
+
+        assert_eq!(debugger.run_until(4), Ok(StopReason::Reached));
+        assert_eq!(debugger.read_wire(6), Some(true));
+        assert_eq!(debugger.read_wire(7), None);
+        assert_eq!(debugger.wire_values().len(), 7);
+
+        assert_eq!(debugger.resume(), Ok(StopReason::Finished));
+        assert_eq!(debugger.step(), Ok(None));
+        assert!(debugger.is_finished());
+        assert_eq!(
+            debugger.outputs(),
+            Ok(HashMap::from([(5, false), (7, true)]))
+        );
+    }
+
+    #[test]
+    fn test_run_until_wire_written() {
+        let mut executor = full_adder();
+        let mut debugger = executor.debug(&inputs()).unwrap();
+
+        assert_eq!(debugger.run_until_wire_written(5), Ok(StopReason::Reached));
+        assert_eq!(debugger.next_component(), Some(3));
+        assert_eq!(debugger.read_wire(5), Some(false));
+
+        // Input wires are never written by components
+        assert_eq!(debugger.run_until_wire_written(0), Ok(StopReason::Finished));
+    }
+
+    #[test]
+    fn test_breakpoints() {
+        let mut executor = full_adder();
+        let mut debugger = executor.debug(&inputs()).unwrap();
+        debugger
+            .set_breakpoint(Breakpoint::Component(2))
+            .set_breakpoint(Breakpoint::Wire(6));
+
+        assert_eq!(
+            debugger.resume(),
+            Ok(StopReason::Breakpoint(Breakpoint::Component(2)))
+        );
+        assert_eq!(debugger.next_component(), Some(2));
+        assert_eq!(
+            debugger.resume(),
+            Ok(StopReason::Breakpoint(Breakpoint::Wire(6)))
+        );
+        assert_eq!(debugger.next_component(), Some(4));
+
+        assert!(debugger.remove_breakpoint(Breakpoint::Wire(6)));
+        assert_eq!(debugger.resume(), Ok(StopReason::Finished));
+    }
+
+    #[test]
+    fn test_breakpoints_on_shared_slot() {
+        // Wires 3 and 4 are merged into the slot of the first AND gate
+        let mut builder = CircuitBuilder::<BooleanGate, bool>::new();
+        builder.add_inputs(&[0, 1]).add_outputs(&[3, 4]);
+        for output in [3, 4] {
+            builder
+                .add_hashed_component(
+                    BooleanGate::new(BooleanOperation::And, vec![0, 1], vec![output]).unwrap(),
+                )
+                .unwrap();
+        }
+        let mut executor = GenericCircuitExecutor::new(builder.build().unwrap());
+        let mut debugger = executor
+            .debug(&HashMap::from([(0, true), (1, true)]))
+            .unwrap();
+        debugger
+            .set_breakpoint(Breakpoint::Wire(4))
+            .set_breakpoint(Breakpoint::Wire(3));
+
+        assert_eq!(
+            debugger.resume(),
+            Ok(StopReason::Breakpoint(Breakpoint::Wire(3)))
+        );
+    }
+
+    #[test]
+    fn test_debug_errors() {
+        let mut builder = CircuitBuilder::<BooleanGate, bool>::new();
+        builder.add_inputs(&[0]);
+        builder
+            .add_component(BooleanGate::new(BooleanOperation::Not, vec![0], vec![1]).unwrap())
+            .unwrap();
+        let mut executor = GenericCircuitExecutor::new(builder.build().unwrap());
+
+        assert_eq!(
+            executor.debug(&HashMap::new()).err(),
+            Some(CircuitExecutionError::InputLengthMismatch)
+        );
+    }
+}
@@ -0,0 +1,292 @@
+//! # Dot Module
+//!
+//! Contains the Graphviz DOT exporter for circuits.
+
+use crate::{circuit::GenericCircuit, model::Component};
+use std::{
+    collections::HashMap,
+    fmt::{self, Write},
+};
+
+/// Component label trait, used to name components in DOT graphs.
+pub trait Label {
+    /// Returns the label of the component.
+    /// Components with an empty label are named by their position in the circuit.
+    fn label(&self) -> String {
+        String::new()
+    }
+
+    /// Returns the graph of the sub-circuit wrapped by the component, if any.
+    /// Sub-circuits can be drawn as clusters instead of single nodes.
+    fn subgraph(&self) -> Option<&dyn DotGraph> {
+        None
+    }
+}
+
+/// Graph that can be written as DOT statements.
+pub trait DotGraph {
+    /// Writes the graph nodes and edges, prefixing node names to keep them unique.
+    fn write_statements(
+        &self,
+        writer: &mut dyn Write,
+        prefix: &str,
+        options: &DotOptions,
+    ) -> fmt::Result;
+}
+
+/// DOT export options.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct DotOptions {
+    /// Draws nested sub-circuits as clusters of their own components.
+    pub cluster_subcircuits: bool,
+}
+
+impl<T, U> GenericCircuit<T, U>
+where
+    T: Component + Label,
+    U: Copy,
+{
+    /// Writes the circuit as a DOT graph.
+    /// Edges are labelled with the original wire id and the memory slot it is mapped to.
+    pub fn write_dot<W: Write>(&self, writer: &mut W, options: &DotOptions) -> fmt::Result {
+        writeln!(writer, "digraph circuit {{")?;
+        writeln!(writer, "  rankdir=LR;")?;
+        self.write_statements(writer, "", options)?;
+        writeln!(writer, "}}")
+    }
+}
+
+impl<T, U> Label for GenericCircuit<T, U>
+where
+    T: Component + Label,
+    U: Copy,
+{
+    fn label(&self) -> String {
+        "CIRCUIT".to_string()
+    }
+
+    fn subgraph(&self) -> Option<&dyn DotGraph> {
+        Some(self)
+    }
+}
+
+impl<T, U> DotGraph for GenericCircuit<T, U>
+where
+    T: Component + Label,
+    U: Copy,
+{
+    fn write_statements(
+        &self,
+        writer: &mut dyn Write,
+        prefix: &str,
+        options: &DotOptions,
+    ) -> fmt::Result {
+        let memory_map = self.memory_map();
+        let component_wires = self.component_wires();
+        let edge = |writer: &mut dyn Write, from: &str, to: &str, wire: usize, slot: usize| {
+            writeln!(writer, "  {from} -> {to} [label=\"{wire} (slot {slot})\"];")
+        };
+
+        // Node driving each memory slot
+        let mut producers = HashMap::new();
+
+        for (position, wire) in self.input_wires().iter().enumerate() {
+            let node = format!("{prefix}in{position}");
+            writeln!(writer, "  {node} [label=\"in {wire}\", shape=invhouse];")?;
+            if let Some(&slot) = memory_map.get(wire) {
+                producers.insert(slot, node);
+            }
+        }
+
+        for (index, (component, (input_wires, _))) in
+            self.components().iter().zip(component_wires).enumerate()
+        {
+            let node = format!("{prefix}c{index}");
+            let label = match component.label() {
+                label if label.is_empty() => index.to_string(),
+                label => escape(&label),
+            };
+
+            match component.subgraph().filter(|_| options.cluster_subcircuits) {
+                Some(graph) => {
+                    writeln!(writer, "  subgraph cluster_{node} {{")?;
+                    writeln!(writer, "  label=\"{label}\";")?;
+                    graph.write_statements(writer, &format!("{node}_"), options)?;
+                    writeln!(writer, "  }}")?;
+
+                    for (position, (&slot, &wire)) in
+                        component.inputs().iter().zip(&input_wires).enumerate()
+                    {
+                        if let Some(producer) = producers.get(&slot) {
+                            edge(
+                                writer,
+                                producer,
+                                &format!("{node}_in{position}"),
+                                wire,
+                                slot,
+                            )?;
+                        }
+                    }
+                    for (position, &slot) in component.outputs().iter().enumerate() {
+                        producers.insert(slot, format!("{node}_out{position}"));
+                    }
+                }
+                None => {
+                    writeln!(writer, "  {node} [label=\"{label}\", shape=box];")?;
+
+                    for (&slot, &wire) in component.inputs().iter().zip(&input_wires) {
+                        if let Some(producer) = producers.get(&slot) {
+                            edge(writer, producer, &node, wire, slot)?;
+                        }
+                    }
+                    for &slot in component.outputs() {
+                        producers.insert(slot, node.clone());
+                    }
+                }
+            }
+        }
+
+        for (position, &wire) in self.output_wires().iter().enumerate() {
+            let node = format!("{prefix}out{position}");
+            writeln!(writer, "  {node} [label=\"out {wire}\", shape=house];")?;
+            if let Some(&slot) = memory_map.get(&wire) {
+                if let Some(producer) = producers.get(&slot) {
+                    edge(writer, producer, &node, wire, slot)?;
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
+
+fn escape(label: &str) -> String {
+    label.replace('\\', "\\\\").replace('"', "\\\"")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        circuit::CircuitBuilder,
+        gates::boolean::{BooleanGate, BooleanOperation},
+    };
+
+    fn and_circuit() -> GenericCircuit<BooleanGate, bool> {
+        let mut builder = CircuitBuilder::<BooleanGate, bool>::new();
+        builder.add_inputs(&[10, 20]);
+        builder
+            .add_component(BooleanGate::new(BooleanOperation::And, vec![10, 20], vec![30]).unwrap())
+            .unwrap();
+        builder.build().unwrap()
+    }
+
+    #[test]
+    fn test_write_dot() {
+        let mut dot = String::new();
+        and_circuit()
+            .write_dot(&mut dot, &DotOptions::default())
+            .unwrap();
+
+        assert_eq!(
+            dot,
+            "digraph circuit {
+  rankdir=LR;
+  in0 [label=\"in 10\", shape=invhouse];
+  in1 [label=\"in 20\", shape=invhouse];
+  c0 [label=\"AND\", shape=box];
+  in0 -> c0 [label=\"10 (slot 0)\"];
+  in1 -> c0 [label=\"20 (slot 1)\"];
+  out0 [label=\"out 30\", shape=house];
+  c0 -> out0 [label=\"30 (slot 2)\"];
+}
+"
+        );
+    }
+
+    #[test]
+    fn test_write_dot_clusters() {
+        let mut inner = and_circuit();
+        inner.set_inputs(vec![0, 1]);
+        inner.set_outputs(vec![2]);
+
+        let mut builder = CircuitBuilder::<GenericCircuit<BooleanGate, bool>, bool>::new();
+        builder.add_inputs(&[0, 1]);
+        builder.add_component(inner).unwrap();
+        let circuit = builder.build().unwrap();
+
+        let mut flat = String::new();
+        circuit
+            .write_dot(&mut flat, &DotOptions::default())
+            .unwrap();
+        assert!(flat.contains("  c0 [label=\"CIRCUIT\", shape=box];"));
+
+        let mut clustered = String::new();
+        let options = DotOptions {
+            cluster_subcircuits: true,
+        };
+        circuit.write_dot(&mut clustered, &options).unwrap();
+        assert!(clustered.contains("  subgraph cluster_c0 {"));
+        assert!(clustered.contains("  c0_c0 [label=\"AND\", shape=box];"));
+        assert!(clustered.contains("  in1 -> c0_in1 [label=\"1 (slot 1)\"];"));
+        assert!(clustered.contains("  c0_out0 -> out0 [label=\"2 (slot 2)\"];"));
+    }
+
+    #[test]
+    fn test_write_dot_after_reuse() {
+        // Computes `NOT (0 XOR 1)`, reusing the slot of input 0 for output 3
+        let mut builder = CircuitBuilder::<BooleanGate, bool>::new();
+        builder.add_inputs(&[0, 1]);
+        builder
+            .add_component(BooleanGate::new(BooleanOperation::Xor, vec![0, 1], vec![2]).unwrap())
+            .unwrap()
+            .add_component(BooleanGate::new(BooleanOperation::Not, vec![2], vec![3]).unwrap())
+            .unwrap();
+        let mut circuit = builder.build().unwrap();
+        circuit.reuse_memory_slots();
+
+        let mut dot = String::new();
+        circuit.write_dot(&mut dot, &DotOptions::default()).unwrap();
+        assert!(dot.contains("  in0 -> c0 [label=\"0 (slot 0)\"];"));
+        assert!(dot.contains("  c0 -> c1 [label=\"2 (slot 2)\"];"));
+        assert!(dot.contains("  c1 -> out0 [label=\"3 (slot 0)\"];"));
+    }
+
+    #[test]
+    fn test_write_dot_unlabelled() {
+        #[derive(Debug)]
+        struct Wire(Vec<usize>, Vec<usize>);
+
+        impl Component for Wire {
+            fn inputs(&self) -> &[usize] {
+                &self.0
+            }
+
+            fn outputs(&self) -> &[usize] {
+                &self.1
+            }
+
+            fn set_inputs(&mut self, inputs: Vec<usize>) {
+                self.0 = inputs;
+            }
+
+            fn set_outputs(&mut self, outputs: Vec<usize>) {
+                self.1 = outputs;
+            }
+        }
+
+        impl Label for Wire {}
+
+        let mut builder = CircuitBuilder::<Wire, bool>::new();
+        builder.add_inputs(&[0]);
+        builder.add_component(Wire(vec![0], vec![1])).unwrap();
+
+        let mut dot = String::new();
+        builder
+            .build()
+            .unwrap()
+            .write_dot(&mut dot, &DotOptions::default())
+            .unwrap();
+        assert!(dot.contains("  c0 [label=\"0\", shape=box];"));
+    }
+}
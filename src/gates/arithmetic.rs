@@ -0,0 +1,651 @@
+//! # Arithmetic Gates Module
+//!
+//! Contains the arithmetic gate library over prime fields.
+
+use crate::{
+    circuit::CircuitMemoryError,
+    dot::Label,
+    model::{Component, Executable, Foldable, Folded, Kind, Memory, StructuralHash},
+};
+use std::{
+    fmt::Debug,
+    hash::Hash,
+    ops::{Add, Mul, Neg, Sub},
+};
+use thiserror::Error;
+
+/// Field element trait.
+pub trait Field:
+    Copy + PartialEq + Add<Output = Self> + Sub<Output = Self> + Mul<Output = Self> + Neg<Output = Self>
+{
+    /// Returns the additive identity.
+    fn zero() -> Self;
+
+    /// Returns the multiplicative identity.
+    fn one() -> Self;
+
+    /// Returns the multiplicative inverse, or `None` for zero.
+    fn inverse(&self) -> Option<Self>;
+}
+
+/// Element of the prime field of order `P`, stored in canonical form.
+/// Every constructor checks that `P` is prime, so composite orders fail to compile.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(try_from = "u64")
+)]
+pub struct PrimeField<const P: u64>(u64);
+
+impl<const P: u64> PrimeField<P> {
+    /// Evaluated by every constructor, as inverses are only correct for prime orders.
+    const PRIME_ORDER: () = assert!(is_prime(P), "the field order must be prime");
+
+    /// Creates a field element, reducing the value modulo `P`.
+    pub fn new(value: u64) -> Self {
+        let () = Self::PRIME_ORDER;
+        Self(value % P)
+    }
+
+    /// Returns the canonical value of the element.
+    pub fn value(&self) -> u64 {
+        self.0
+    }
+
+    /// Raises the element to the given power.
+    pub fn pow(&self, mut exponent: u64) -> Self {
+        let mut base = *self;
+        let mut result = Self::one();
+        while exponent > 0 {
+            if exponent & 1 == 1 {
+                result = result * base;
+            }
+            base = base * base;
+            exponent >>= 1;
+        }
+        result
+    }
+}
+
+impl<const P: u64> Default for PrimeField<P> {
+    fn default() -> Self {
+        Self::new(0)
+    }
+}
+
+/// Accepts canonical values only, such as deserialized elements.
+impl<const P: u64> TryFrom<u64> for PrimeField<P> {
+    type Error = FieldError;
+
+    fn try_from(value: u64) -> Result<Self, Self::Error> {
+        match value < P {
+            true => Ok(Self::new(value)),
+            false => Err(FieldError::NonCanonical(value, P)),
+        }
+    }
+}
+
+/// Returns whether `n` is prime, with a Miller-Rabin test over bases that make it
+/// deterministic for every 64-bit integer.
+const fn is_prime(n: u64) -> bool {
+    const BASES: [u64; 12] = [2, 3, 5, 7, 11, 13, 17, 19, 23, 29, 31, 37];
+
+    let mut index = 0;
+    while index < BASES.len() {
+        if n.is_multiple_of(BASES[index]) {
+            return n == BASES[index];
+        }
+        index += 1;
+    }
+    if n < 2 {
+        return false;
+    }
+
+    // n - 1 = d * 2^s, with d odd
+    let mut d = n - 1;
+    let mut s = 0;
+    while d.is_multiple_of(2) {
+        d /= 2;
+        s += 1;
+    }
+
+    let mut index = 0;
+    'bases: while index < BASES.len() {
+        let mut x = pow_mod(BASES[index], d, n);
+        index += 1;
+        if x == 1 || x == n - 1 {
+            continue;
+        }
+        let mut round = 1;
+        while round < s {
+            x = mul_mod(x, x, n);
+            if x == n - 1 {
+                continue 'bases;
+            }
+            round += 1;
+        }
+        return false;
+    }
+
+    true
+}
+
+const fn mul_mod(a: u64, b: u64, modulus: u64) -> u64 {
+    ((a as u128 * b as u128) % modulus as u128) as u64
+}
+
+const fn pow_mod(mut base: u64, mut exponent: u64, modulus: u64) -> u64 {
+    let mut result = 1;
+    base %= modulus;
+    while exponent > 0 {
+        if exponent & 1 == 1 {
+            result = mul_mod(result, base, modulus);
+        }
+        base = mul_mod(base, base, modulus);
+        exponent >>= 1;
+    }
+    result
+}
+
+impl<const P: u64> Add for PrimeField<P> {
+    type Output = Self;
+
+    fn add(self, rhs: Self) -> Self {
+        Self(((self.0 as u128 + rhs.0 as u128) % P as u128) as u64)
+    }
+}
+
+impl<const P: u64> Sub for PrimeField<P> {
+    type Output = Self;
+
+    fn sub(self, rhs: Self) -> Self {
+        self + -rhs
+    }
+}
+
+impl<const P: u64> Mul for PrimeField<P> {
+    type Output = Self;
+
+    fn mul(self, rhs: Self) -> Self {
+        Self(((self.0 as u128 * rhs.0 as u128) % P as u128) as u64)
+    }
+}
+
+impl<const P: u64> Neg for PrimeField<P> {
+    type Output = Self;
+
+    fn neg(self) -> Self {
+        Self((P - self.0) % P)
+    }
+}
+
+impl<const P: u64> Field for PrimeField<P> {
+    fn zero() -> Self {
+        Self::new(0)
+    }
+
+    fn one() -> Self {
+        Self::new(1)
+    }
+
+    /// Computes the inverse using Fermat's little theorem.
+    fn inverse(&self) -> Option<Self> {
+        match self.0 {
+            0 => None,
+            _ => Some(self.pow(P - 2)),
+        }
+    }
+}
+
+/// Arithmetic gate operations.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum ArithOperation<F> {
+    Add,
+    Sub,
+    Mul,
+    Neg,
+    Inverse,
+    /// Multiplies the input by a constant.
+    ConstMul(F),
+    /// Drives a constant value, without inputs.
+    Const(F),
+    /// Checks that both inputs are equal and forwards their value.
+    AssertEq,
+}
+
+impl<F> ArithOperation<F> {
+    /// Returns the number of inputs taken by the operation.
+    pub fn arity(&self) -> usize {
+        match self {
+            Self::Const(_) => 0,
+            Self::Neg | Self::Inverse | Self::ConstMul(_) => 1,
+            Self::Add | Self::Sub | Self::Mul | Self::AssertEq => 2,
+        }
+    }
+}
+
+/// Arithmetic gate with a single output.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ArithGate<F> {
+    op: ArithOperation<F>,
+    inputs: Vec<usize>,
+    outputs: Vec<usize>,
+}
+
+impl<F: Field> ArithGate<F> {
+    /// Creates a new arithmetic gate, checking the number of inputs and outputs.
+    pub fn new(
+        op: ArithOperation<F>,
+        inputs: Vec<usize>,
+        outputs: Vec<usize>,
+    ) -> Result<Self, ArithGateError> {
+        if inputs.len() != op.arity() {
+            return Err(ArithGateError::InputArityMismatch {
+                expected: op.arity(),
+                found: inputs.len(),
+            });
+        }
+        if outputs.len() != 1 {
+            return Err(ArithGateError::OutputArityMismatch(outputs.len()));
+        }
+
+        Ok(Self {
+            op,
+            inputs,
+            outputs,
+        })
+    }
+
+    /// Returns the gate operation.
+    pub fn op(&self) -> ArithOperation<F> {
+        self.op
+    }
+
+    /// Reads the gate input at the given position.
+    fn read<M>(&self, memory: &M, position: usize) -> Result<F, ArithGateError>
+    where
+        M: Memory<F, Error = CircuitMemoryError>,
+    {
+        let index = self
+            .inputs
+            .get(position)
+            .ok_or(ArithGateError::InputArityMismatch {
+                expected: self.op.arity(),
+                found: self.inputs.len(),
+            })?;
+
+        Ok(memory.read(*index)?)
+    }
+}
+
+impl<F> Component for ArithGate<F> {
+    fn inputs(&self) -> &[usize] {
+        &self.inputs
+    }
+
+    fn outputs(&self) -> &[usize] {
+        &self.outputs
+    }
+
+    fn set_inputs(&mut self, inputs: Vec<usize>) {
+        self.inputs = inputs;
+    }
+
+    fn set_outputs(&mut self, outputs: Vec<usize>) {
+        self.outputs = outputs;
+    }
+}
+
+impl<F, M> Executable<F, M> for ArithGate<F>
+where
+    F: Field,
+    M: Memory<F, Error = CircuitMemoryError>,
+{
+    type Error = ArithGateError;
+
+    fn execute(&self, memory: &mut M) -> Result<(), Self::Error> {
+        let result = match self.op {
+            ArithOperation::Add => self.read(memory, 0)? + self.read(memory, 1)?,
+            ArithOperation::Sub => self.read(memory, 0)? - self.read(memory, 1)?,
+            ArithOperation::Mul => self.read(memory, 0)? * self.read(memory, 1)?,
+            ArithOperation::Neg => -self.read(memory, 0)?,
+            ArithOperation::Inverse => self
+                .read(memory, 0)?
+                .inverse()
+                .ok_or(ArithGateError::DivisionByZero(self.inputs[0]))?,
+            ArithOperation::ConstMul(constant) => self.read(memory, 0)? * constant,
+            ArithOperation::Const(value) => value,
+            ArithOperation::AssertEq => {
+                let value = self.read(memory, 0)?;
+                if value != self.read(memory, 1)? {
+                    return Err(ArithGateError::NotEqual(self.inputs[0], self.inputs[1]));
+                }
+                value
+            }
+        };
+
+        let output = self
+            .outputs
+            .first()
+            .ok_or(ArithGateError::OutputArityMismatch(0))?;
+
+        Ok(memory.write(*output, result)?)
+    }
+
+    /// Reports the wires of failed inversions and assertions with the provided translation.
+    fn map_error_indices(error: Self::Error, translate: &dyn Fn(usize) -> usize) -> Self::Error {
+        match error {
+            ArithGateError::DivisionByZero(index) => {
+                ArithGateError::DivisionByZero(translate(index))
+            }
+            ArithGateError::NotEqual(left, right) => {
+                ArithGateError::NotEqual(translate(left), translate(right))
+            }
+            error => error,
+        }
+    }
+}
+
+impl<F: Field> Foldable<F> for ArithGate<F> {
+    /// Folds known inputs, simplifying identity and absorbing constants such as `x + 0`
+    /// or `x * 0`. Inversions of zero and failing assertions are kept, to fail at execution.
+    fn fold(&self, known: &[Option<F>]) -> Folded<F, Self> {
+        if known.len() != self.op.arity() {
+            return Folded::Unchanged;
+        }
+
+        let replaced = |op, inputs| {
+            Folded::Replaced(Self {
+                op,
+                inputs,
+                outputs: self.outputs.clone(),
+            })
+        };
+
+        match (self.op, known) {
+            (ArithOperation::Const(value), _) => Folded::Constant(vec![value]),
+            (ArithOperation::Add, [Some(a), Some(b)]) => Folded::Constant(vec![*a + *b]),
+            (ArithOperation::Sub, [Some(a), Some(b)]) => Folded::Constant(vec![*a - *b]),
+            (ArithOperation::Mul, [Some(a), Some(b)]) => Folded::Constant(vec![*a * *b]),
+            (ArithOperation::Neg, [Some(a)]) => Folded::Constant(vec![-*a]),
+            (ArithOperation::Inverse, [Some(a)]) => match a.inverse() {
+                Some(inverse) => Folded::Constant(vec![inverse]),
+                None => Folded::Unchanged,
+            },
+            (ArithOperation::ConstMul(constant), [Some(a)]) => {
+                Folded::Constant(vec![*a * constant])
+            }
+            (ArithOperation::AssertEq, [Some(a), Some(b)]) if a == b => Folded::Constant(vec![*a]),
+            (ArithOperation::Add, [Some(zero), None]) if *zero == F::zero() => Folded::Identity(1),
+            (ArithOperation::Add | ArithOperation::Sub, [None, Some(zero)])
+                if *zero == F::zero() =>
+            {
+                Folded::Identity(0)
+            }
+            (ArithOperation::Sub, [Some(zero), None]) if *zero == F::zero() => {
+                replaced(ArithOperation::Neg, vec![self.inputs[1]])
+            }
+            (ArithOperation::Mul, [Some(constant), None] | [None, Some(constant)]) => {
+                let other = usize::from(known[0].is_some());
+                if *constant == F::zero() {
+                    Folded::Constant(vec![F::zero()])
+                } else if *constant == F::one() {
+                    Folded::Identity(other)
+                } else {
+                    replaced(
+                        ArithOperation::ConstMul(*constant),
+                        vec![self.inputs[other]],
+                    )
+                }
+            }
+            (ArithOperation::ConstMul(constant), [None]) if constant == F::zero() => {
+                Folded::Constant(vec![F::zero()])
+            }
+            (ArithOperation::ConstMul(constant), [None]) if constant == F::one() => {
+                Folded::Identity(0)
+            }
+            _ => Folded::Unchanged,
+        }
+    }
+
+    fn constant(value: F, output: usize) -> Option<Self> {
+        Self::new(ArithOperation::Const(value), vec![], vec![output]).ok()
+    }
+}
+
+impl<F: Copy + Hash + Eq> StructuralHash for ArithGate<F> {
+    type Key = ArithOperation<F>;
+
+    fn structural_key(&self) -> Self::Key {
+        self.op
+    }
+
+    fn is_commutative(&self) -> bool {
+        matches!(
+            self.op,
+            ArithOperation::Add | ArithOperation::Mul | ArithOperation::AssertEq
+        )
+    }
+}
+
+impl<F> Kind for ArithGate<F> {
+    fn kind(&self) -> &str {
+        match self.op {
+            ArithOperation::Add => "ADD",
+            ArithOperation::Sub => "SUB",
+            ArithOperation::Mul => "MUL",
+            ArithOperation::Neg => "NEG",
+            ArithOperation::Inverse => "INV",
+            ArithOperation::ConstMul(_) => "CONST_MUL",
+            ArithOperation::Const(_) => "CONST",
+            ArithOperation::AssertEq => "ASSERT_EQ",
+        }
+    }
+
+    fn is_multiplicative(&self) -> bool {
+        matches!(self.op, ArithOperation::Mul | ArithOperation::Inverse)
+    }
+}
+
+impl<F: Debug> Label for ArithGate<F> {
+    fn label(&self) -> String {
+        match &self.op {
+            ArithOperation::ConstMul(constant) => format!("MUL BY {constant:?}"),
+            ArithOperation::Const(value) => format!("CONST {value:?}"),
+            _ => self.kind().to_string(),
+        }
+    }
+}
+
+#[derive(Debug, Error, PartialEq, Eq)]
+pub enum ArithGateError {
+    #[error("Gate expects {expected} inputs, found {found}")]
+    InputArityMismatch { expected: usize, found: usize },
+    #[error("Gate expects a single output, found {0}")]
+    OutputArityMismatch(usize),
+    #[error("Division by zero: value at index {0} has no inverse")]
+    DivisionByZero(usize),
+    #[error("Values at indices {0} and {1} are not equal")]
+    NotEqual(usize, usize),
+    #[error("Circuit memory error: {0}")]
+    MemoryError(#[from] CircuitMemoryError),
+}
+
+#[derive(Debug, Error, PartialEq, Eq)]
+pub enum FieldError {
+    #[error("Value {0} is not reduced modulo the field order {1}")]
+    NonCanonical(u64, u64),
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::circuit::{
+        CircuitBuilder, CircuitExecutionError, CircuitMemory, GenericCircuitExecutor,
+    };
+    use std::collections::HashMap;
+
+    type F = PrimeField<97>;
+
+    fn evaluate(op: ArithOperation<F>, inputs: &[u64]) -> Result<F, ArithGateError> {
+        let mut memory = CircuitMemory::new(inputs.len() + 1);
+        for (index, &value) in inputs.iter().enumerate() {
+            memory.write(index, F::new(value))?;
+        }
+
+        let gate = ArithGate::new(op, (0..inputs.len()).collect(), vec![inputs.len()])?;
+        gate.execute(&mut memory)?;
+
+        Ok(memory.read(inputs.len())?)
+    }
+
+    #[test]
+    fn test_field_operations() {
+        assert_eq!(F::new(90) + F::new(10), F::new(3));
+        assert_eq!(F::new(3) - F::new(10), F::new(90));
+        assert_eq!(F::new(50) * F::new(2), F::new(3));
+        assert_eq!(-F::zero(), F::zero());
+        assert_eq!(
+            F::new(5).inverse().map(|inverse| inverse * F::new(5)),
+            Some(F::one())
+        );
+        assert_eq!(F::zero().inverse(), None);
+    }
+
+    #[test]
+    fn test_primality() {
+        let primes = [2, 3, 97, 65_537, 2_305_843_009_213_693_951, u64::MAX - 58];
+        assert!(primes.into_iter().all(is_prime));
+
+        // Carmichael numbers and strong pseudoprimes to the smallest bases are composite
+        let composites = [0, 1, 4, 9, 561, 3_215_031_751, 3_825_123_056_546_413_051];
+        assert!(!composites.into_iter().any(is_prime));
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_deserialize_canonical() {
+        assert_eq!(serde_json::from_str::<F>("96").unwrap(), F::new(96));
+        assert!(serde_json::from_str::<F>("97").is_err());
+        assert_eq!(F::try_from(100), Err(FieldError::NonCanonical(100, 97)));
+    }
+
+    #[test]
+    fn test_gate_operations() {
+        assert_eq!(evaluate(ArithOperation::Add, &[96, 2]), Ok(F::new(1)));
+        assert_eq!(evaluate(ArithOperation::Sub, &[1, 2]), Ok(F::new(96)));
+        assert_eq!(evaluate(ArithOperation::Mul, &[10, 10]), Ok(F::new(3)));
+        assert_eq!(evaluate(ArithOperation::Neg, &[1]), Ok(F::new(96)));
+        assert_eq!(evaluate(ArithOperation::Inverse, &[2]), Ok(F::new(49)));
+        assert_eq!(
+            evaluate(ArithOperation::ConstMul(F::new(3)), &[4]),
+            Ok(F::new(12))
+        );
+        assert_eq!(evaluate(ArithOperation::AssertEq, &[7, 7]), Ok(F::new(7)));
+        assert_eq!(
+            evaluate(ArithOperation::AssertEq, &[7, 8]),
+            Err(ArithGateError::NotEqual(0, 1))
+        );
+        assert_eq!(
+            evaluate(ArithOperation::Inverse, &[0]),
+            Err(ArithGateError::DivisionByZero(0))
+        );
+    }
+
+    #[test]
+    fn test_fold() {
+        let gate = |op, inputs| ArithGate::<F>::new(op, inputs, vec![9]).unwrap();
+        let mul = gate(ArithOperation::Mul, vec![0, 1]);
+
+        assert_eq!(
+            mul.fold(&[Some(F::new(3)), Some(F::new(4))]),
+            Folded::Constant(vec![F::new(12)])
+        );
+        assert_eq!(
+            mul.fold(&[None, Some(F::zero())]),
+            Folded::Constant(vec![F::zero()])
+        );
+        assert_eq!(mul.fold(&[Some(F::one()), None]), Folded::Identity(1));
+        assert_eq!(
+            mul.fold(&[Some(F::new(5)), None]),
+            Folded::Replaced(gate(ArithOperation::ConstMul(F::new(5)), vec![1]))
+        );
+        assert_eq!(
+            gate(ArithOperation::Add, vec![0, 1]).fold(&[None, Some(F::zero())]),
+            Folded::Identity(0)
+        );
+        assert_eq!(
+            gate(ArithOperation::Sub, vec![0, 1]).fold(&[Some(F::zero()), None]),
+            Folded::Replaced(gate(ArithOperation::Neg, vec![1]))
+        );
+        assert_eq!(
+            gate(ArithOperation::Inverse, vec![0]).fold(&[Some(F::zero())]),
+            Folded::Unchanged
+        );
+        assert_eq!(
+            ArithGate::constant(F::new(7), 9),
+            Some(gate(ArithOperation::Const(F::new(7)), vec![]))
+        );
+    }
+
+    #[test]
+    fn test_arity_checks() {
+        assert_eq!(
+            ArithGate::<F>::new(ArithOperation::Mul, vec![0], vec![1]),
+            Err(ArithGateError::InputArityMismatch {
+                expected: 2,
+                found: 1,
+            })
+        );
+        assert_eq!(
+            ArithGate::<F>::new(ArithOperation::Neg, vec![0], vec![1, 2]),
+            Err(ArithGateError::OutputArityMismatch(2))
+        );
+    }
+
+    #[test]
+    fn test_circuit_division() {
+        // Computes (a + b) / c
+        let mut builder = CircuitBuilder::<ArithGate<F>, F>::new();
+        builder.add_inputs(&[0, 1, 2]);
+
+        builder
+            .add_component(ArithGate::new(ArithOperation::Add, vec![0, 1], vec![3]).unwrap())
+            .unwrap()
+            .add_component(ArithGate::new(ArithOperation::Inverse, vec![2], vec![4]).unwrap())
+            .unwrap()
+            .add_component(ArithGate::new(ArithOperation::Mul, vec![3, 4], vec![5]).unwrap())
+            .unwrap();
+
+        let mut executor = GenericCircuitExecutor::new(builder.build().unwrap());
+
+        let output = executor
+            .run(&HashMap::from([
+                (0, F::new(4)),
+                (1, F::new(2)),
+                (2, F::new(3)),
+            ]))
+            .unwrap();
+        assert_eq!(output, HashMap::from([(5, F::new(2))]));
+
+        let error = executor
+            .run(&HashMap::from([
+                (0, F::new(4)),
+                (1, F::new(2)),
+                (2, F::zero()),
+            ]))
+            .unwrap_err();
+        assert_eq!(
+            error,
+            CircuitExecutionError::ComponentExecutionError {
+                index: 1,
+                inputs: vec![2],
+                outputs: vec![4],
+                source: ArithGateError::DivisionByZero(2),
+            }
+        );
+    }
+}
@@ -0,0 +1,726 @@
+//! # Boolean Gates Module
+//!
+//! Contains the standard boolean gate library.
+
+use crate::{
+    bristol::{BristolComponent, BristolOperation},
+    circuit::CircuitMemoryError,
+    dot::Label,
+    model::{Component, Executable, Foldable, Folded, Kind, Memory, StructuralHash},
+};
+use std::ops::{BitAnd, BitOr, BitXor, Not};
+use thiserror::Error;
+
+/// Boolean wire value type, closed under the boolean operations.
+pub trait Logic:
+    Copy + BitAnd<Output = Self> + BitOr<Output = Self> + BitXor<Output = Self> + Not<Output = Self>
+{
+    /// Returns the constant `value`, on every lane of bit-sliced types.
+    fn splat(value: bool) -> Self;
+
+    /// Returns `when_true` where the value is set and `when_false` elsewhere.
+    fn select(self, when_false: Self, when_true: Self) -> Self {
+        (!self & when_false) | (self & when_true)
+    }
+}
+
+/// Bit-sliced boolean value type.
+/// Integer types are bit-sliced: every bit is an independent lane, so a gate evaluates
+/// `LANES` inputs at once.
+pub trait BitLanes: Logic {
+    /// Number of independent evaluations carried by a value.
+    const LANES: usize;
+
+    /// Returns the value of a lane.
+    fn lane(self, lane: usize) -> bool;
+
+    /// Returns the value with a lane set to `value`.
+    fn with_lane(self, lane: usize, value: bool) -> Self;
+}
+
+impl Logic for bool {
+    fn splat(value: bool) -> Self {
+        value
+    }
+}
+
+impl BitLanes for bool {
+    const LANES: usize = 1;
+
+    fn lane(self, _lane: usize) -> bool {
+        self
+    }
+
+    fn with_lane(self, _lane: usize, value: bool) -> Self {
+        value
+    }
+}
+
+macro_rules! impl_bit_lanes {
+    ($($word:ty),*) => {
+        $(
+            impl Logic for $word {
+                fn splat(value: bool) -> Self {
+                    if value {
+                        !0
+                    } else {
+                        0
+                    }
+                }
+            }
+
+            impl BitLanes for $word {
+                const LANES: usize = <$word>::BITS as usize;
+
+                fn lane(self, lane: usize) -> bool {
+                    (self >> lane) & 1 == 1
+                }
+
+                fn with_lane(self, lane: usize, value: bool) -> Self {
+                    (self & !(1 << lane)) | (Self::from(value) << lane)
+                }
+            }
+        )*
+    };
+}
+
+impl_bit_lanes!(u8, u16, u32, u64, u128);
+
+/// Three-valued logic value, where `X` is unknown.
+/// Operations propagate `X` only when the result depends on it, so `0 AND X` is `0`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Trit {
+    Zero,
+    One,
+    X,
+}
+
+impl Trit {
+    /// Returns the known value, or `None` for `X`.
+    pub fn to_bool(self) -> Option<bool> {
+        match self {
+            Self::Zero => Some(false),
+            Self::One => Some(true),
+            Self::X => None,
+        }
+    }
+}
+
+impl From<bool> for Trit {
+    fn from(value: bool) -> Self {
+        match value {
+            false => Self::Zero,
+            true => Self::One,
+        }
+    }
+}
+
+impl BitAnd for Trit {
+    type Output = Self;
+
+    fn bitand(self, rhs: Self) -> Self {
+        match (self, rhs) {
+            (Self::Zero, _) | (_, Self::Zero) => Self::Zero,
+            (Self::One, Self::One) => Self::One,
+            _ => Self::X,
+        }
+    }
+}
+
+impl BitOr for Trit {
+    type Output = Self;
+
+    fn bitor(self, rhs: Self) -> Self {
+        match (self, rhs) {
+            (Self::One, _) | (_, Self::One) => Self::One,
+            (Self::Zero, Self::Zero) => Self::Zero,
+            _ => Self::X,
+        }
+    }
+}
+
+impl BitXor for Trit {
+    type Output = Self;
+
+    fn bitxor(self, rhs: Self) -> Self {
+        match (self.to_bool(), rhs.to_bool()) {
+            (Some(a), Some(b)) => Self::from(a ^ b),
+            _ => Self::X,
+        }
+    }
+}
+
+impl Not for Trit {
+    type Output = Self;
+
+    fn not(self) -> Self {
+        match self {
+            Self::Zero => Self::One,
+            Self::One => Self::Zero,
+            Self::X => Self::X,
+        }
+    }
+}
+
+impl Logic for Trit {
+    fn splat(value: bool) -> Self {
+        Self::from(value)
+    }
+
+    /// Selecting between equal values is known even with an unknown select.
+    fn select(self, when_false: Self, when_true: Self) -> Self {
+        match self {
+            Self::Zero => when_false,
+            Self::One => when_true,
+            Self::X if when_false == when_true => when_false,
+            Self::X => Self::X,
+        }
+    }
+}
+
+/// Boolean gate operations.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum BooleanOperation {
+    And,
+    Or,
+    Xor,
+    Nand,
+    Nor,
+    Xnor,
+    Not,
+    Buf,
+    /// Drives a constant value, without inputs.
+    Const(bool),
+    /// Selects between the second (select unset) and third (select set) inputs
+    /// using the first one.
+    Mux,
+}
+
+impl BooleanOperation {
+    /// Returns the number of inputs taken by the operation.
+    pub fn arity(&self) -> usize {
+        match self {
+            Self::Const(_) => 0,
+            Self::Not | Self::Buf => 1,
+            Self::And | Self::Or | Self::Xor | Self::Nand | Self::Nor | Self::Xnor => 2,
+            Self::Mux => 3,
+        }
+    }
+
+    /// Applies the operation to the input values, in input order.
+    fn apply<L: Logic>(&self, inputs: &[L]) -> L {
+        match self {
+            Self::And => inputs[0] & inputs[1],
+            Self::Or => inputs[0] | inputs[1],
+            Self::Xor => inputs[0] ^ inputs[1],
+            Self::Nand => !(inputs[0] & inputs[1]),
+            Self::Nor => !(inputs[0] | inputs[1]),
+            Self::Xnor => !(inputs[0] ^ inputs[1]),
+            Self::Not => !inputs[0],
+            Self::Buf => inputs[0],
+            Self::Const(value) => L::splat(*value),
+            Self::Mux => inputs[0].select(inputs[1], inputs[2]),
+        }
+    }
+}
+
+/// Boolean gate with a single output.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct BooleanGate {
+    op: BooleanOperation,
+    inputs: Vec<usize>,
+    outputs: Vec<usize>,
+}
+
+impl BooleanGate {
+    /// Creates a new boolean gate, checking the number of inputs and outputs.
+    pub fn new(
+        op: BooleanOperation,
+        inputs: Vec<usize>,
+        outputs: Vec<usize>,
+    ) -> Result<Self, BooleanGateError> {
+        if inputs.len() != op.arity() {
+            return Err(BooleanGateError::InputArityMismatch {
+                op,
+                expected: op.arity(),
+                found: inputs.len(),
+            });
+        }
+        if outputs.len() != 1 {
+            return Err(BooleanGateError::OutputArityMismatch {
+                op,
+                found: outputs.len(),
+            });
+        }
+
+        Ok(Self {
+            op,
+            inputs,
+            outputs,
+        })
+    }
+
+    /// Returns the gate operation.
+    pub fn op(&self) -> BooleanOperation {
+        self.op
+    }
+
+    /// Reads the gate input at the given position.
+    fn read<L, M>(&self, memory: &M, position: usize) -> Result<L, BooleanGateError>
+    where
+        M: Memory<L, Error = CircuitMemoryError>,
+    {
+        let index = self
+            .inputs
+            .get(position)
+            .ok_or(BooleanGateError::InputArityMismatch {
+                op: self.op,
+                expected: self.op.arity(),
+                found: self.inputs.len(),
+            })?;
+
+        Ok(memory.read(*index)?)
+    }
+}
+
+impl Component for BooleanGate {
+    fn inputs(&self) -> &[usize] {
+        &self.inputs
+    }
+
+    fn outputs(&self) -> &[usize] {
+        &self.outputs
+    }
+
+    fn set_inputs(&mut self, inputs: Vec<usize>) {
+        self.inputs = inputs;
+    }
+
+    fn set_outputs(&mut self, outputs: Vec<usize>) {
+        self.outputs = outputs;
+    }
+}
+
+impl<L, M> Executable<L, M> for BooleanGate
+where
+    L: Logic,
+    M: Memory<L, Error = CircuitMemoryError>,
+{
+    type Error = BooleanGateError;
+
+    fn execute(&self, memory: &mut M) -> Result<(), Self::Error> {
+        let mut values = [L::splat(false); 3];
+        for (position, value) in values.iter_mut().take(self.op.arity()).enumerate() {
+            *value = self.read(memory, position)?;
+        }
+        let result = self.op.apply(&values);
+
+        let output = self
+            .outputs
+            .first()
+            .ok_or(BooleanGateError::OutputArityMismatch {
+                op: self.op,
+                found: 0,
+            })?;
+
+        Ok(memory.write(*output, result)?)
+    }
+}
+
+impl Foldable<bool> for BooleanGate {
+    /// Folds known inputs, simplifying identity and absorbing constants such as `x AND 1`
+    /// or `x OR 1`.
+    fn fold(&self, known: &[Option<bool>]) -> Folded<bool, Self> {
+        if known.len() != self.op.arity() {
+            return Folded::Unchanged;
+        }
+        if let Some(values) = known.iter().copied().collect::<Option<Vec<_>>>() {
+            return Folded::Constant(vec![self.op.apply(&values)]);
+        }
+
+        let not = |position: usize| {
+            Folded::Replaced(Self {
+                op: BooleanOperation::Not,
+                inputs: vec![self.inputs[position]],
+                outputs: self.outputs.clone(),
+            })
+        };
+
+        match self.op {
+            BooleanOperation::Buf => Folded::Identity(0),
+            BooleanOperation::Mux => match known[0] {
+                Some(false) => Folded::Identity(1),
+                Some(true) => Folded::Identity(2),
+                None => Folded::Unchanged,
+            },
+            BooleanOperation::And
+            | BooleanOperation::Or
+            | BooleanOperation::Xor
+            | BooleanOperation::Nand
+            | BooleanOperation::Nor
+            | BooleanOperation::Xnor => {
+                let (value, other) = match (known[0], known[1]) {
+                    (Some(value), None) => (value, 1),
+                    (None, Some(value)) => (value, 0),
+                    _ => return Folded::Unchanged,
+                };
+
+                match (self.op, value) {
+                    (BooleanOperation::And, false) | (BooleanOperation::Nor, true) => {
+                        Folded::Constant(vec![false])
+                    }
+                    (BooleanOperation::Or, true) | (BooleanOperation::Nand, false) => {
+                        Folded::Constant(vec![true])
+                    }
+                    (BooleanOperation::And, true)
+                    | (BooleanOperation::Or, false)
+                    | (BooleanOperation::Xor, false)
+                    | (BooleanOperation::Xnor, true) => Folded::Identity(other),
+                    _ => not(other),
+                }
+            }
+            _ => Folded::Unchanged,
+        }
+    }
+
+    fn constant(value: bool, output: usize) -> Option<Self> {
+        Self::new(BooleanOperation::Const(value), vec![], vec![output]).ok()
+    }
+}
+
+impl StructuralHash for BooleanGate {
+    type Key = BooleanOperation;
+
+    fn structural_key(&self) -> Self::Key {
+        self.op
+    }
+
+    fn is_commutative(&self) -> bool {
+        matches!(
+            self.op,
+            BooleanOperation::And
+                | BooleanOperation::Or
+                | BooleanOperation::Xor
+                | BooleanOperation::Nand
+                | BooleanOperation::Nor
+                | BooleanOperation::Xnor
+        )
+    }
+}
+
+impl Kind for BooleanGate {
+    fn kind(&self) -> &str {
+        match self.op {
+            BooleanOperation::And => "AND",
+            BooleanOperation::Or => "OR",
+            BooleanOperation::Xor => "XOR",
+            BooleanOperation::Nand => "NAND",
+            BooleanOperation::Nor => "NOR",
+            BooleanOperation::Xnor => "XNOR",
+            BooleanOperation::Not => "NOT",
+            BooleanOperation::Buf => "BUF",
+            BooleanOperation::Const(_) => "CONST",
+            BooleanOperation::Mux => "MUX",
+        }
+    }
+
+    /// Gates that need an AND gate when built from AND, XOR and NOT gates.
+    fn is_multiplicative(&self) -> bool {
+        matches!(
+            self.op,
+            BooleanOperation::And
+                | BooleanOperation::Or
+                | BooleanOperation::Nand
+                | BooleanOperation::Nor
+                | BooleanOperation::Mux
+        )
+    }
+}
+
+impl Label for BooleanGate {
+    fn label(&self) -> String {
+        match self.op {
+            BooleanOperation::Const(value) => format!("CONST {}", u8::from(value)),
+            _ => self.kind().to_string(),
+        }
+    }
+}
+
+impl BristolComponent for BooleanGate {
+    fn from_bristol(op: BristolOperation, inputs: Vec<usize>, outputs: Vec<usize>) -> Option<Self> {
+        let op = match op {
+            BristolOperation::And => BooleanOperation::And,
+            BristolOperation::Xor => BooleanOperation::Xor,
+            BristolOperation::Inv => BooleanOperation::Not,
+            BristolOperation::Eq(value) => BooleanOperation::Const(value),
+            BristolOperation::Eqw => BooleanOperation::Buf,
+        };
+
+        Self::new(op, inputs, outputs).ok()
+    }
+
+    fn bristol_operation(&self) -> Option<BristolOperation> {
+        match self.op {
+            BooleanOperation::And => Some(BristolOperation::And),
+            BooleanOperation::Xor => Some(BristolOperation::Xor),
+            BooleanOperation::Not => Some(BristolOperation::Inv),
+            BooleanOperation::Const(value) => Some(BristolOperation::Eq(value)),
+            BooleanOperation::Buf => Some(BristolOperation::Eqw),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Error, PartialEq, Eq)]
+pub enum BooleanGateError {
+    #[error("{op:?} gate expects {expected} inputs, found {found}")]
+    InputArityMismatch {
+        op: BooleanOperation,
+        expected: usize,
+        found: usize,
+    },
+    #[error("{op:?} gate expects a single output, found {found}")]
+    OutputArityMismatch { op: BooleanOperation, found: usize },
+    #[error("Circuit memory error: {0}")]
+    MemoryError(#[from] CircuitMemoryError),
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+    use crate::circuit::{
+        CircuitBuilder, CircuitExecutionError, CircuitMemory, GenericCircuit,
+        GenericCircuitExecutor,
+    };
+    use std::collections::HashMap;
+
+    /// Builds a circuit of boolean gates, each given by its operation, input wires and output
+    /// wire. The outputs are derived from the gates when none are declared.
+    pub(crate) fn circuit<U: Copy>(
+        inputs: &[usize],
+        outputs: &[usize],
+        gates: impl IntoIterator<Item = (BooleanOperation, Vec<usize>, usize)>,
+    ) -> GenericCircuit<BooleanGate, U> {
+        let mut builder = CircuitBuilder::new();
+        builder.add_inputs(inputs).add_outputs(outputs);
+        for (op, inputs, output) in gates {
+            builder
+                .add_component(BooleanGate::new(op, inputs, vec![output]).unwrap())
+                .unwrap();
+        }
+
+        builder.build().unwrap()
+    }
+
+    /// Full adder with inputs 0, 1, 2, sum 5 and carry 7.
+    pub(crate) fn full_adder<U: Copy>() -> GenericCircuit<BooleanGate, U> {
+        circuit(
+            &[0, 1, 2],
+            &[5, 7],
+            [
+                (BooleanOperation::Xor, vec![0, 1], 3),
+                (BooleanOperation::And, vec![0, 1], 4),
+                (BooleanOperation::Xor, vec![3, 2], 5),
+                (BooleanOperation::And, vec![3, 2], 6),
+                (BooleanOperation::Or, vec![4, 6], 7),
+            ],
+        )
+    }
+
+    fn evaluate(op: BooleanOperation, inputs: &[bool]) -> Result<bool, BooleanGateError> {
+        let mut memory = CircuitMemory::new(inputs.len() + 1);
+        for (index, &value) in inputs.iter().enumerate() {
+            memory.write(index, value)?;
+        }
+
+        let gate = BooleanGate::new(op, (0..inputs.len()).collect(), vec![inputs.len()])?;
+        gate.execute(&mut memory)?;
+
+        Ok(memory.read(inputs.len())?)
+    }
+
+    #[test]
+    fn test_truth_tables() {
+        let pairs = [(false, false), (false, true), (true, false), (true, true)];
+        let tables = [
+            (BooleanOperation::And, [false, false, false, true]),
+            (BooleanOperation::Or, [false, true, true, true]),
+            (BooleanOperation::Xor, [false, true, true, false]),
+            (BooleanOperation::Nand, [true, true, true, false]),
+            (BooleanOperation::Nor, [true, false, false, false]),
+            (BooleanOperation::Xnor, [true, false, false, true]),
+        ];
+
+        for (op, expected) in tables {
+            for ((a, b), result) in pairs.into_iter().zip(expected) {
+                assert_eq!(evaluate(op, &[a, b]), Ok(result), "{op:?}({a}, {b})");
+            }
+        }
+
+        assert_eq!(evaluate(BooleanOperation::Not, &[true]), Ok(false));
+        assert_eq!(evaluate(BooleanOperation::Buf, &[true]), Ok(true));
+        assert_eq!(evaluate(BooleanOperation::Const(true), &[]), Ok(true));
+        assert_eq!(evaluate(BooleanOperation::Const(false), &[]), Ok(false));
+        assert_eq!(
+            evaluate(BooleanOperation::Mux, &[false, true, false]),
+            Ok(true)
+        );
+        assert_eq!(
+            evaluate(BooleanOperation::Mux, &[true, true, false]),
+            Ok(false)
+        );
+    }
+
+    #[test]
+    fn test_arity_checks() {
+        assert_eq!(
+            BooleanGate::new(BooleanOperation::Not, vec![0, 1], vec![2]),
+            Err(BooleanGateError::InputArityMismatch {
+                op: BooleanOperation::Not,
+                expected: 1,
+                found: 2,
+            })
+        );
+        assert_eq!(
+            BooleanGate::new(BooleanOperation::And, vec![0, 1], vec![]),
+            Err(BooleanGateError::OutputArityMismatch {
+                op: BooleanOperation::And,
+                found: 0,
+            })
+        );
+    }
+
+    #[test]
+    fn test_uninitialized_input() {
+        let mut memory = CircuitMemory::new(3);
+        memory.write(0, true).unwrap();
+
+        let gate = BooleanGate::new(BooleanOperation::And, vec![0, 1], vec![2]).unwrap();
+
+        assert_eq!(
+            gate.execute(&mut memory),
+            Err(BooleanGateError::MemoryError(
+                CircuitMemoryError::UninitializedSlot(1)
+            ))
+        );
+    }
+
+    #[test]
+    fn test_fold() {
+        let gate = |op, inputs| BooleanGate::new(op, inputs, vec![9]).unwrap();
+        let and = gate(BooleanOperation::And, vec![0, 1]);
+        let xor = gate(BooleanOperation::Xor, vec![0, 1]);
+
+        assert_eq!(and.fold(&[None, None]), Folded::Unchanged);
+        assert_eq!(
+            and.fold(&[Some(true), Some(true)]),
+            Folded::Constant(vec![true])
+        );
+        assert_eq!(
+            and.fold(&[None, Some(false)]),
+            Folded::Constant(vec![false])
+        );
+        assert_eq!(and.fold(&[Some(true), None]), Folded::Identity(1));
+        assert_eq!(xor.fold(&[None, Some(false)]), Folded::Identity(0));
+        assert_eq!(
+            xor.fold(&[Some(true), None]),
+            Folded::Replaced(gate(BooleanOperation::Not, vec![1]))
+        );
+        assert_eq!(
+            gate(BooleanOperation::Mux, vec![0, 1, 2]).fold(&[Some(true), None, None]),
+            Folded::Identity(2)
+        );
+        assert_eq!(
+            BooleanGate::constant(true, 9),
+            Some(gate(BooleanOperation::Const(true), vec![]))
+        );
+    }
+
+    #[test]
+    fn test_circuit_with_constants() {
+        let mut builder = CircuitBuilder::<BooleanGate, bool>::new();
+        builder.add_inputs(&[0, 1]).add_outputs(&[4]);
+
+        builder
+            .add_component(
+                BooleanGate::new(BooleanOperation::Const(true), vec![], vec![2]).unwrap(),
+            )
+            .unwrap()
+            .add_component(BooleanGate::new(BooleanOperation::Nand, vec![0, 1], vec![3]).unwrap())
+            .unwrap()
+            .add_component(BooleanGate::new(BooleanOperation::Mux, vec![3, 2, 1], vec![4]).unwrap())
+            .unwrap();
+
+        let mut executor = GenericCircuitExecutor::new(builder.build().unwrap());
+
+        let output = executor
+            .run(&HashMap::from([(0, true), (1, true)]))
+            .unwrap();
+        assert_eq!(output, HashMap::from([(4, true)]));
+
+        let output = executor
+            .run(&HashMap::from([(0, false), (1, false)]))
+            .unwrap();
+        assert_eq!(output, HashMap::from([(4, false)]));
+    }
+
+    #[test]
+    fn test_trit_operations() {
+        use Trit::{One, Zero, X};
+
+        assert_eq!(Zero & X, Zero);
+        assert_eq!(One & X, X);
+        assert_eq!(One | X, One);
+        assert_eq!(Zero | X, X);
+        assert_eq!(X ^ One, X);
+        assert_eq!(!X, X);
+        assert_eq!(X.select(One, One), One);
+        assert_eq!(X.select(Zero, One), X);
+        assert_eq!(One.select(X, Zero), Zero);
+    }
+
+    #[test]
+    fn test_unknown_inputs() {
+        // Outputs 0 AND 1 on wire 3, 1 OR 2 on wire 4 and 0 XOR 2 on wire 5
+        let mut executor = GenericCircuitExecutor::new(circuit::<Trit>(
+            &[0, 1, 2],
+            &[3, 4, 5],
+            [
+                (BooleanOperation::And, vec![0, 1], 3),
+                (BooleanOperation::Or, vec![1, 2], 4),
+                (BooleanOperation::Xor, vec![0, 2], 5),
+            ],
+        ));
+        let inputs = HashMap::from([(1, Trit::Zero)]);
+        assert_eq!(
+            executor.run(&inputs),
+            Err(CircuitExecutionError::InputLengthMismatch)
+        );
+
+        executor.set_missing_input(Some(Trit::X));
+        assert_eq!(
+            executor.run(&inputs),
+            Ok(HashMap::from([(3, Trit::Zero), (4, Trit::X), (5, Trit::X)]))
+        );
+
+        let inputs = HashMap::from([(1, Trit::Zero), (2, Trit::One)]);
+        assert_eq!(
+            executor.run(&inputs),
+            Ok(HashMap::from([
+                (3, Trit::Zero),
+                (4, Trit::One),
+                (5, Trit::X)
+            ]))
+        );
+
+        // Values for wires that are not circuit inputs are still rejected
+        let inputs = HashMap::from([(1, Trit::Zero), (7, Trit::One)]);
+        assert_eq!(
+            executor.run(&inputs),
+            Err(CircuitExecutionError::InputLengthMismatch)
+        );
+    }
+}
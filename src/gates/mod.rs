@@ -0,0 +1,6 @@
+//! # Gates Module
+//!
+//! Contains ready to use component libraries.
+
+pub mod arithmetic;
+pub mod boolean;
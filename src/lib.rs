@@ -2,5 +2,19 @@
 //!
 //! Circuit building utilities.
 
+pub mod batch;
+pub mod bristol;
 pub mod circuit;
+pub mod debugger;
+pub mod dot;
+pub mod gates;
+pub mod memory;
 pub mod model;
+pub mod observer;
+pub mod parallel;
+pub mod passes;
+pub mod sequential;
+#[cfg(feature = "serde")]
+pub mod serialization;
+pub mod stats;
+pub mod vcd;
@@ -0,0 +1,368 @@
+//! # Memory Module
+//!
+//! Contains specialized memory implementations.
+
+use crate::{
+    circuit::CircuitMemoryError,
+    model::{Memory, SizedMemory},
+};
+use std::sync::OnceLock;
+
+#[cfg(feature = "serde")]
+use crate::serialization::DataError;
+
+const WORD_BITS: usize = u64::BITS as usize;
+
+/// Bit-packed boolean memory.
+/// Values and their initialization flags are packed into `u64` words, 64 slots per word.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(try_from = "BitMemoryData")
+)]
+pub struct BitMemory {
+    size: usize,
+    values: Vec<u64>,
+    initialized: Vec<u64>,
+}
+
+/// Serialized fields of a bit memory, checked for consistency before the memory is loaded.
+#[cfg(feature = "serde")]
+#[derive(serde::Deserialize)]
+struct BitMemoryData {
+    size: usize,
+    values: Vec<u64>,
+    initialized: Vec<u64>,
+}
+
+#[cfg(feature = "serde")]
+impl TryFrom<BitMemoryData> for BitMemory {
+    type Error = DataError;
+
+    fn try_from(data: BitMemoryData) -> Result<Self, Self::Error> {
+        let expected = data.size.div_ceil(WORD_BITS);
+        for words in [data.values.len(), data.initialized.len()] {
+            if words != expected {
+                return Err(DataError::WordCountMismatch {
+                    expected,
+                    found: words,
+                });
+            }
+        }
+
+        // Values of uninitialized slots are cleared, so writes can set their bits
+        if let Some(word) = data
+            .values
+            .iter()
+            .zip(&data.initialized)
+            .position(|(values, initialized)| values & !initialized != 0)
+        {
+            return Err(DataError::UninitializedValue(word));
+        }
+
+        Ok(Self {
+            size: data.size,
+            values: data.values,
+            initialized: data.initialized,
+        })
+    }
+}
+
+impl BitMemory {
+    /// Creates a new memory with the specified number of empty slots.
+    pub fn new(size: usize) -> Self {
+        let words = size.div_ceil(WORD_BITS);
+
+        Self {
+            size,
+            values: vec![0; words],
+            initialized: vec![0; words],
+        }
+    }
+
+    /// Returns the number of slots.
+    pub fn size(&self) -> usize {
+        self.size
+    }
+
+    /// Clears every memory slot, keeping the allocation for the next execution.
+    pub fn clear(&mut self) {
+        self.values.fill(0);
+        self.initialized.fill(0);
+    }
+
+    /// Returns the word index and bit mask of a slot.
+    fn locate(index: usize) -> (usize, u64) {
+        (index / WORD_BITS, 1 << (index % WORD_BITS))
+    }
+}
+
+impl SizedMemory<bool> for BitMemory {
+    fn with_size(size: usize) -> Self {
+        Self::new(size)
+    }
+
+    fn clear(&mut self) {
+        BitMemory::clear(self);
+    }
+
+    fn release(&mut self, index: usize) {
+        if index < self.size {
+            let (word, mask) = Self::locate(index);
+            self.initialized[word] &= !mask;
+            self.values[word] &= !mask;
+        }
+    }
+}
+
+impl Memory<bool> for BitMemory {
+    type Error = CircuitMemoryError;
+
+    /// Attempts to read a value from the specified memory index.
+    /// Returns an error if the index is out of bounds or if no value has been written there yet.
+    fn read(&self, index: usize) -> Result<bool, Self::Error> {
+        if index >= self.size {
+            return Err(CircuitMemoryError::ReadError(index));
+        }
+
+        let (word, mask) = Self::locate(index);
+        if self.initialized[word] & mask == 0 {
+            return Err(CircuitMemoryError::UninitializedSlot(index));
+        }
+
+        Ok(self.values[word] & mask != 0)
+    }
+
+    /// Writes a value to the specified memory index.
+    /// Returns an error if the index is out of bounds or if the slot is already occupied.
+    fn write(&mut self, index: usize, value: bool) -> Result<(), Self::Error> {
+        if index >= self.size {
+            return Err(CircuitMemoryError::WriteError(index));
+        }
+
+        let (word, mask) = Self::locate(index);
+        if self.initialized[word] & mask != 0 {
+            return Err(CircuitMemoryError::RewriteAttempt(index));
+        }
+
+        self.initialized[word] |= mask;
+        if value {
+            self.values[word] |= mask;
+        }
+
+        Ok(())
+    }
+}
+
+/// Write-once memory supporting concurrent writes to disjoint slots.
+/// Components write through a shared reference, `&SharedMemory` being a memory itself.
+#[derive(Debug, Default)]
+pub struct SharedMemory<T> {
+    slots: Vec<OnceLock<T>>,
+}
+
+impl<T> SharedMemory<T> {
+    /// Creates a new memory with the specified number of empty slots.
+    pub fn new(size: usize) -> Self {
+        Self {
+            slots: (0..size).map(|_| OnceLock::new()).collect(),
+        }
+    }
+
+    /// Returns the number of slots.
+    pub fn size(&self) -> usize {
+        self.slots.len()
+    }
+
+    /// Clears every memory slot, keeping the allocation for the next execution.
+    pub fn clear(&mut self) {
+        self.slots.iter_mut().for_each(|slot| {
+            slot.take();
+        });
+    }
+
+    /// Reads a value from the specified memory index.
+    fn read_slot(&self, index: usize) -> Result<T, CircuitMemoryError>
+    where
+        T: Copy,
+    {
+        match self.slots.get(index) {
+            Some(slot) => slot
+                .get()
+                .copied()
+                .ok_or(CircuitMemoryError::UninitializedSlot(index)),
+            None => Err(CircuitMemoryError::ReadError(index)),
+        }
+    }
+
+    /// Writes a value to the specified memory index, through a shared reference.
+    fn write_slot(&self, index: usize, value: T) -> Result<(), CircuitMemoryError> {
+        match self.slots.get(index) {
+            Some(slot) => slot
+                .set(value)
+                .map_err(|_| CircuitMemoryError::RewriteAttempt(index)),
+            None => Err(CircuitMemoryError::WriteError(index)),
+        }
+    }
+}
+
+impl<T: Copy> SizedMemory<T> for SharedMemory<T> {
+    fn with_size(size: usize) -> Self {
+        Self::new(size)
+    }
+
+    fn clear(&mut self) {
+        SharedMemory::clear(self);
+    }
+
+    fn release(&mut self, index: usize) {
+        if let Some(slot) = self.slots.get_mut(index) {
+            slot.take();
+        }
+    }
+}
+
+impl<T: Copy> Memory<T> for SharedMemory<T> {
+    type Error = CircuitMemoryError;
+
+    fn read(&self, index: usize) -> Result<T, Self::Error> {
+        self.read_slot(index)
+    }
+
+    fn write(&mut self, index: usize, value: T) -> Result<(), Self::Error> {
+        self.write_slot(index, value)
+    }
+}
+
+impl<T: Copy> Memory<T> for &SharedMemory<T> {
+    type Error = CircuitMemoryError;
+
+    fn read(&self, index: usize) -> Result<T, Self::Error> {
+        self.read_slot(index)
+    }
+
+    fn write(&mut self, index: usize, value: T) -> Result<(), Self::Error> {
+        self.write_slot(index, value)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        circuit::{CircuitBuilder, GenericCircuit, GenericCircuitExecutor},
+        gates::boolean::{BooleanGate, BooleanOperation},
+        model::Component,
+    };
+    use std::collections::HashMap;
+
+    #[test]
+    fn test_memory_operations() {
+        let mut memory = BitMemory::new(130);
+
+        assert_eq!(
+            memory.read(1),
+            Err(CircuitMemoryError::UninitializedSlot(1))
+        );
+
+        assert_eq!(memory.write(2, true), Ok(()));
+        assert_eq!(memory.write(64, false), Ok(()));
+        assert_eq!(memory.write(129, true), Ok(()));
+        assert_eq!(memory.read(2), Ok(true));
+        assert_eq!(memory.read(64), Ok(false));
+        assert_eq!(memory.read(129), Ok(true));
+
+        assert_eq!(
+            memory.write(130, true),
+            Err(CircuitMemoryError::WriteError(130))
+        );
+        assert_eq!(memory.read(130), Err(CircuitMemoryError::ReadError(130)));
+        assert_eq!(
+            memory.write(64, true),
+            Err(CircuitMemoryError::RewriteAttempt(64))
+        );
+
+        memory.clear();
+        assert_eq!(
+            memory.read(2),
+            Err(CircuitMemoryError::UninitializedSlot(2))
+        );
+    }
+
+    #[test]
+    fn test_executor_with_bit_memory() {
+        let mut builder = CircuitBuilder::<BooleanGate, bool>::new();
+        builder.add_inputs(&[0, 1, 2]);
+        builder
+            .add_component(BooleanGate::new(BooleanOperation::Nand, vec![0, 1], vec![3]).unwrap())
+            .unwrap()
+            .add_component(BooleanGate::new(BooleanOperation::Xor, vec![3, 2], vec![4]).unwrap())
+            .unwrap();
+
+        let mut executor =
+            GenericCircuitExecutor::<_, _, BitMemory>::with_memory(builder.build().unwrap());
+
+        let output = executor
+            .run(&HashMap::from([(0, true), (1, true), (2, true)]))
+            .unwrap();
+        assert_eq!(output, HashMap::from([(4, true)]));
+
+        let output = executor
+            .run(&HashMap::from([(0, false), (1, true), (2, true)]))
+            .unwrap();
+        assert_eq!(output, HashMap::from([(4, false)]));
+    }
+
+    #[test]
+    fn test_nested_circuit_with_bit_memory() {
+        let mut builder = CircuitBuilder::<BooleanGate, bool>::new();
+        builder.add_inputs(&[0, 1]);
+        builder
+            .add_component(BooleanGate::new(BooleanOperation::Xor, vec![0, 1], vec![2]).unwrap())
+            .unwrap();
+        let mut xor_circuit = builder.build().unwrap();
+        xor_circuit.set_inputs(vec![10, 11]);
+        xor_circuit.set_outputs(vec![12]);
+
+        let mut builder = CircuitBuilder::<GenericCircuit<BooleanGate, bool>, bool>::new();
+        builder.add_inputs(&[10, 11]);
+        builder.add_component(xor_circuit).unwrap();
+
+        let mut executor =
+            GenericCircuitExecutor::<_, _, BitMemory>::with_memory(builder.build().unwrap());
+        let output = executor
+            .run(&HashMap::from([(10, true), (11, false)]))
+            .unwrap();
+        assert_eq!(output, HashMap::from([(12, true)]));
+    }
+
+    #[test]
+    fn test_shared_memory_concurrent_writes() {
+        let mut memory = SharedMemory::new(8);
+
+        std::thread::scope(|scope| {
+            for half in 0..2 {
+                let mut view = &memory;
+                scope.spawn(move || {
+                    for index in (half * 4)..(half * 4 + 4) {
+                        view.write(index, index * 10).unwrap();
+                    }
+                });
+            }
+        });
+
+        assert_eq!(memory.read(5), Ok(50));
+        assert_eq!(
+            (&memory).write(5, 0),
+            Err(CircuitMemoryError::RewriteAttempt(5))
+        );
+        assert_eq!(memory.read(8), Err(CircuitMemoryError::ReadError(8)));
+
+        memory.clear();
+        assert_eq!(
+            memory.read(5),
+            Err(CircuitMemoryError::UninitializedSlot(5))
+        );
+    }
+}
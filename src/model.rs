@@ -2,6 +2,9 @@
 //!
 //! Contains the library model traits.
 
+use crate::stats::CircuitStats;
+use std::hash::Hash;
+
 /// Memory trait.
 pub trait Memory<T> {
     type Error;
@@ -13,6 +16,18 @@ pub trait Memory<T> {
     fn write(&mut self, index: usize, value: T) -> Result<(), Self::Error>;
 }
 
+/// Memory with a fixed number of slots, that can be allocated and reused by executors.
+pub trait SizedMemory<T>: Memory<T> {
+    /// Creates a memory with the specified number of empty slots.
+    fn with_size(size: usize) -> Self;
+
+    /// Clears every slot, keeping the allocation.
+    fn clear(&mut self);
+
+    /// Clears a single slot, so it can be written again.
+    fn release(&mut self, index: usize);
+}
+
 /// Circuit component trait.
 pub trait Component {
     /// Returns the indices of the input nodes.
@@ -34,4 +49,66 @@ pub trait Executable<T, U: Memory<T>>: Component {
 
     /// Executes the component using the provided memory.
     fn execute(&self, memory: &mut U) -> Result<(), Self::Error>;
+
+    /// Translates the memory indices carried by an execution error, using the provided
+    /// translation, such as back to the original wire ids. Errors are kept as is by default.
+    fn map_error_indices(error: Self::Error, _translate: &dyn Fn(usize) -> usize) -> Self::Error {
+        error
+    }
+}
+
+/// Outcome of partially evaluating a component with some inputs known.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Folded<T, C> {
+    /// Every output has a known value, in output order.
+    Constant(Vec<T>),
+    /// The single output always equals the input at the given position.
+    Identity(usize),
+    /// The component can be replaced by a simpler one over the same wires.
+    Replaced(C),
+    /// The component cannot be simplified.
+    Unchanged,
+}
+
+/// Constant folding trait, describing how to partially evaluate a component.
+pub trait Foldable<T>: Component + Sized {
+    /// Partially evaluates the component, given the known value of each input, in input order.
+    fn fold(&self, known: &[Option<T>]) -> Folded<T, Self>;
+
+    /// Returns a component without inputs driving `value` on the `output` wire,
+    /// if the component type can express constants.
+    fn constant(value: T, output: usize) -> Option<Self>;
+}
+
+/// Structural hashing trait, identifying components that compute the same function of their
+/// inputs.
+pub trait StructuralHash: Component {
+    /// Key identifying the function computed by the component, such as its operation.
+    type Key: Hash + Eq;
+
+    /// Returns the structural key of the component.
+    fn structural_key(&self) -> Self::Key;
+
+    /// Returns whether the inputs can be reordered without changing the outputs.
+    fn is_commutative(&self) -> bool {
+        false
+    }
+}
+
+/// Component kind trait, used to classify components in circuit reports.
+pub trait Kind {
+    /// Returns the kind of the component, such as the gate name.
+    fn kind(&self) -> &str;
+
+    /// Returns whether the component is multiplicative, such as AND or MUL gates.
+    /// Multiplicative components determine the multiplicative depth of a circuit.
+    fn is_multiplicative(&self) -> bool {
+        false
+    }
+
+    /// Returns the statistics of the sub-circuit wrapped by the component, if any.
+    /// Circuit statistics then count the components of the sub-circuit instead.
+    fn nested_stats(&self) -> Option<CircuitStats> {
+        None
+    }
 }
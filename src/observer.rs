@@ -0,0 +1,243 @@
+//! # Observer Module
+//!
+//! Contains the execution observer trait and the observed memory wrapper.
+
+use crate::model::{Memory, SizedMemory};
+use std::marker::PhantomData;
+
+/// Execution observer trait, notified by executors while a circuit runs.
+/// Every callback does nothing by default, so observers only implement the ones they need.
+/// Memory accesses are reported by memory slot; `GenericCircuit::memory_map` maps original
+/// wire ids to slots.
+pub trait ExecutionObserver<T, U> {
+    /// Called before the component at `index` in execution order is executed.
+    fn before_component(&mut self, _index: usize, _component: &T) {}
+
+    /// Called after the component at `index` has executed successfully.
+    fn after_component(&mut self, _index: usize, _component: &T) {}
+
+    /// Called after a value is written to a memory slot, including circuit input values.
+    fn on_write(&mut self, _slot: usize, _value: U) {}
+
+    /// Called after a value is read from a memory slot.
+    /// Reads go through a shared memory reference, so observers record them with interior
+    /// mutability.
+    fn on_read(&self, _slot: usize, _value: U) {}
+}
+
+/// Observer ignoring every event.
+/// Passed by value to `run_observed`, components execute over the memory itself, as in `run`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct NoopObserver;
+
+impl<T, U> ExecutionObserver<T, U> for NoopObserver {}
+
+/// Observation of a single run, choosing the memory the components execute over.
+/// A mutable reference to an `ExecutionObserver` executes over an `ObservedMemory`
+/// reporting to it, while `NoopObserver` executes over the memory itself.
+pub trait Observation<'a, T, U, M: 'a> {
+    /// Memory the components execute over.
+    type Memory: Memory<U>;
+
+    /// Calls `run` with the observed memory.
+    fn observe<R>(self, memory: &'a mut M, run: impl FnOnce(&mut Self::Memory) -> R) -> R;
+
+    /// Called before the component at `index` in execution order is executed.
+    fn before_component(_memory: &mut Self::Memory, _index: usize, _component: &T) {}
+
+    /// Called after the component at `index` has executed successfully.
+    fn after_component(_memory: &mut Self::Memory, _index: usize, _component: &T) {}
+
+    /// Releases a memory slot without notifying the observer.
+    fn release(memory: &mut Self::Memory, slot: usize);
+
+    /// Returns the observed memory, for unobserved accesses.
+    fn memory(memory: &Self::Memory) -> &M;
+}
+
+impl<'a, T, U, M> Observation<'a, T, U, M> for NoopObserver
+where
+    M: SizedMemory<U> + 'a,
+{
+    type Memory = M;
+
+    fn observe<R>(self, memory: &'a mut M, run: impl FnOnce(&mut M) -> R) -> R {
+        run(memory)
+    }
+
+    fn release(memory: &mut M, slot: usize) {
+        memory.release(slot);
+    }
+
+    fn memory(memory: &M) -> &M {
+        memory
+    }
+}
+
+impl<'a, 'o: 'a, T, U, M, O> Observation<'a, T, U, M> for &'o mut O
+where
+    U: Copy,
+    M: SizedMemory<U> + 'a,
+    O: ExecutionObserver<T, U>,
+{
+    type Memory = ObservedMemory<'a, T, M, O>;
+
+    fn observe<R>(self, memory: &'a mut M, run: impl FnOnce(&mut Self::Memory) -> R) -> R {
+        run(&mut ObservedMemory::new(memory, self))
+    }
+
+    fn before_component(memory: &mut Self::Memory, index: usize, component: &T) {
+        memory.observer().before_component(index, component);
+    }
+
+    fn after_component(memory: &mut Self::Memory, index: usize, component: &T) {
+        memory.observer().after_component(index, component);
+    }
+
+    fn release(memory: &mut Self::Memory, slot: usize) {
+        memory.release::<U>(slot);
+    }
+
+    fn memory(memory: &Self::Memory) -> &M {
+        memory.memory()
+    }
+}
+
+/// Memory reporting the reads and writes of components to an observer.
+pub struct ObservedMemory<'a, T, M, O> {
+    memory: &'a mut M,
+    observer: &'a mut O,
+    _phantom: PhantomData<fn(&T)>,
+}
+
+impl<'a, T, M, O> ObservedMemory<'a, T, M, O> {
+    /// Wraps a memory, reporting its accesses to the observer.
+    pub fn new(memory: &'a mut M, observer: &'a mut O) -> Self {
+        Self {
+            memory,
+            observer,
+            _phantom: PhantomData,
+        }
+    }
+
+    /// Returns the wrapped memory, for unobserved accesses.
+    pub fn memory(&self) -> &M {
+        self.memory
+    }
+
+    /// Returns the observer.
+    pub fn observer(&mut self) -> &mut O {
+        self.observer
+    }
+
+    /// Releases a memory slot without notifying the observer.
+    pub(crate) fn release<U>(&mut self, index: usize)
+    where
+        M: SizedMemory<U>,
+    {
+        self.memory.release(index);
+    }
+}
+
+impl<T, U, M, O> Memory<U> for ObservedMemory<'_, T, M, O>
+where
+    U: Copy,
+    M: Memory<U>,
+    O: ExecutionObserver<T, U>,
+{
+    type Error = M::Error;
+
+    fn read(&self, index: usize) -> Result<U, Self::Error> {
+        let value = self.memory.read(index)?;
+        self.observer.on_read(index, value);
+        Ok(value)
+    }
+
+    fn write(&mut self, index: usize, value: U) -> Result<(), Self::Error> {
+        self.memory.write(index, value)?;
+        self.observer.on_write(index, value);
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        circuit::{CircuitBuilder, CircuitExecutionError, GenericCircuitExecutor},
+        gates::boolean::{BooleanGate, BooleanOperation},
+    };
+    use std::{cell::Cell, collections::HashMap};
+
+    /// Counts the executions of each gate and every memory access.
+    #[derive(Default)]
+    struct Profiler {
+        executions: HashMap<BooleanOperation, usize>,
+        started: Vec<usize>,
+        writes: Vec<(usize, bool)>,
+        reads: Cell<usize>,
+    }
+
+    impl ExecutionObserver<BooleanGate, bool> for Profiler {
+        fn before_component(&mut self, index: usize, _component: &BooleanGate) {
+            self.started.push(index);
+        }
+
+        fn after_component(&mut self, _index: usize, component: &BooleanGate) {
+            *self.executions.entry(component.op()).or_default() += 1;
+        }
+
+        fn on_write(&mut self, slot: usize, value: bool) {
+            self.writes.push((slot, value));
+        }
+
+        fn on_read(&self, _slot: usize, _value: bool) {
+            self.reads.set(self.reads.get() + 1);
+        }
+    }
+
+    fn half_adder() -> GenericCircuitExecutor<BooleanGate, bool> {
+        let mut builder = CircuitBuilder::<BooleanGate, bool>::new();
+        builder.add_inputs(&[0, 1]);
+        builder
+            .add_component(BooleanGate::new(BooleanOperation::Xor, vec![0, 1], vec![2]).unwrap())
+            .unwrap()
+            .add_component(BooleanGate::new(BooleanOperation::And, vec![0, 1], vec![3]).unwrap())
+            .unwrap();
+
+        GenericCircuitExecutor::new(builder.build().unwrap())
+    }
+
+    #[test]
+    fn test_observer_callbacks() {
+        let mut executor = half_adder();
+        let inputs = HashMap::from([(0, true), (1, false)]);
+        let expected = executor.run(&inputs);
+
+        let mut profiler = Profiler::default();
+        assert_eq!(executor.run_observed(&inputs, &mut profiler), expected);
+        assert_eq!(profiler.started, [0, 1]);
+        assert_eq!(
+            profiler.executions,
+            HashMap::from([(BooleanOperation::Xor, 1), (BooleanOperation::And, 1)])
+        );
+        assert_eq!(profiler.writes.len(), 4);
+        assert!(profiler.writes.contains(&(2, true)));
+        assert_eq!(profiler.reads.get(), 4);
+    }
+
+    #[test]
+    fn test_noop_observer() {
+        let mut executor = half_adder();
+        let inputs = HashMap::from([(0, true), (1, true)]);
+        let expected = executor.run(&inputs);
+        assert_eq!(executor.run_observed(&inputs, &mut NoopObserver), expected);
+        assert_eq!(executor.run_observed(&inputs, NoopObserver), expected);
+
+        let inputs = HashMap::from([(0, true)]);
+        assert_eq!(
+            executor.run_observed(&inputs, &mut NoopObserver),
+            Err(CircuitExecutionError::InputLengthMismatch)
+        );
+    }
+}
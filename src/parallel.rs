@@ -0,0 +1,225 @@
+//! # Parallel Module
+//!
+//! Contains the level-parallel circuit executor.
+
+use crate::{
+    circuit::{GenericCircuit, RunResult},
+    memory::SharedMemory,
+    model::{Component, Executable, SizedMemory},
+};
+use std::{collections::HashMap, num::NonZeroUsize, panic, thread};
+
+/// Default number of components below which a level is executed sequentially.
+pub const DEFAULT_PARALLEL_THRESHOLD: usize = 1024;
+
+/// Executes a circuit level by level, running the components of each topological level in
+/// parallel over a `SharedMemory`.
+/// Components of the same level never depend on each other, so they write disjoint slots.
+/// Circuits with reused memory slots are executed sequentially, in their original order, with
+/// one level per component.
+pub struct LevelExecutor<T, U> {
+    circuit: GenericCircuit<T, U>,
+    levels: Vec<Vec<usize>>,
+    memory: SharedMemory<U>,
+    threshold: usize,
+    workers: usize,
+}
+
+impl<T, U, E> LevelExecutor<T, U>
+where
+    T: Component + Sync + for<'a> Executable<U, &'a SharedMemory<U>, Error = E>,
+    U: Copy + Send + Sync,
+    E: Send,
+{
+    /// Creates a new level executor, grouping the circuit components by the topological levels
+    /// computed when the circuit was built.
+    /// Uses one worker per available CPU and the default parallel threshold.
+    pub fn new(circuit: GenericCircuit<T, U>) -> Self {
+        let mut levels = Vec::new();
+        if circuit.releases().is_empty() {
+            for (index, &level) in circuit.levels().iter().enumerate() {
+                if levels.len() <= level {
+                    levels.resize_with(level + 1, Vec::new);
+                }
+                levels[level].push(index);
+            }
+        } else {
+            // Reused memory slots are only valid in the original execution order
+            levels = (0..circuit.components().len())
+                .map(|index| vec![index])
+                .collect();
+        }
+
+        Self {
+            memory: SharedMemory::new(circuit.memory_size()),
+            circuit,
+            levels,
+            threshold: DEFAULT_PARALLEL_THRESHOLD,
+            workers: thread::available_parallelism().map_or(1, NonZeroUsize::get),
+        }
+    }
+
+    /// Sets the number of components below which a level is executed sequentially.
+    pub fn set_threshold(&mut self, threshold: usize) -> &mut Self {
+        self.threshold = threshold;
+        self
+    }
+
+    /// Sets the number of worker threads used for each parallel level.
+    pub fn set_workers(&mut self, workers: usize) -> &mut Self {
+        self.workers = workers.max(1);
+        self
+    }
+
+    /// Returns the component indices of each level, in execution order.
+    /// Circuits with reused memory slots have a single component per level.
+    pub fn levels(&self) -> &[Vec<usize>] {
+        &self.levels
+    }
+
+    /// Runs the circuit using the provided input values and returns a map of the output values.
+    /// When components of a level fail, the error of the first failing worker is returned.
+    pub fn run(&mut self, inputs: &HashMap<usize, U>) -> RunResult<U, E> {
+        self.memory.clear();
+
+        let circuit = &self.circuit;
+        circuit.write_inputs(&mut &self.memory, inputs, None)?;
+
+        for level in &self.levels {
+            // Single components, including those of circuits with reused slots, run in place
+            if level.len() < self.threshold.max(2) || self.workers == 1 {
+                for &index in level {
+                    circuit.execute_component(index, &mut &self.memory)?;
+                    for &slot in circuit.releases().get(index).into_iter().flatten() {
+                        self.memory.release(slot);
+                    }
+                }
+                continue;
+            }
+
+            let memory = &self.memory;
+            let chunk_size = level.len().div_ceil(self.workers);
+            thread::scope(|scope| {
+                let handles = level
+                    .chunks(chunk_size)
+                    .map(|chunk| {
+                        scope.spawn(move || {
+                            let mut memory = memory;
+                            chunk.iter().try_for_each(|&index| {
+                                circuit.execute_component(index, &mut memory)
+                            })
+                        })
+                    })
+                    .collect::<Vec<_>>();
+
+                handles.into_iter().try_for_each(|handle| {
+                    handle
+                        .join()
+                        .unwrap_or_else(|error| panic::resume_unwind(error))
+                })
+            })?;
+        }
+
+        circuit.read_outputs(&self.memory)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        circuit::{CircuitBuilder, CircuitExecutionError, GenericCircuitExecutor},
+        gates::boolean::{BooleanGate, BooleanOperation},
+    };
+
+    /// XOR reduction tree over `width` inputs, with an AND gate on every pair of inputs.
+    fn reduction_tree(width: usize) -> GenericCircuit<BooleanGate, bool> {
+        let mut builder = CircuitBuilder::<BooleanGate, bool>::new();
+        let inputs = (0..width).collect::<Vec<_>>();
+        builder.add_inputs(&inputs);
+
+        let mut next = width;
+        let mut layer = inputs;
+        let mut outputs = Vec::new();
+        while layer.len() > 1 {
+            let mut reduced = Vec::new();
+            for pair in layer.chunks(2) {
+                if let [a, b] = *pair {
+                    builder
+                        .add_component(
+                            BooleanGate::new(BooleanOperation::Xor, vec![a, b], vec![next])
+                                .unwrap(),
+                        )
+                        .unwrap()
+                        .add_component(
+                            BooleanGate::new(BooleanOperation::And, vec![a, b], vec![next + 1])
+                                .unwrap(),
+                        )
+                        .unwrap();
+                    reduced.push(next);
+                    outputs.push(next + 1);
+                    next += 2;
+                } else {
+                    reduced.push(pair[0]);
+                }
+            }
+            layer = reduced;
+        }
+        outputs.extend(layer);
+        builder.add_outputs(&outputs);
+
+        builder.build().unwrap()
+    }
+
+    #[test]
+    fn test_levels() {
+        let executor = LevelExecutor::new(reduction_tree(4));
+        assert_eq!(executor.levels(), &[vec![0, 1, 2, 3], vec![4, 5]]);
+    }
+
+    #[test]
+    fn test_matches_sequential_execution() {
+        let inputs = (0..100)
+            .map(|wire| (wire, wire % 3 == 0 || wire % 7 == 0))
+            .collect::<HashMap<_, _>>();
+
+        let mut sequential = GenericCircuitExecutor::new(reduction_tree(100));
+        let expected = sequential.run(&inputs).unwrap();
+
+        let mut executor = LevelExecutor::new(reduction_tree(100));
+        assert_eq!(executor.run(&inputs), Ok(expected.clone()));
+
+        executor.set_threshold(2).set_workers(4);
+        assert_eq!(executor.run(&inputs), Ok(expected.clone()));
+        assert_eq!(executor.run(&inputs), Ok(expected));
+    }
+
+    #[test]
+    fn test_reused_memory_slots() {
+        let inputs = (0..16).map(|wire| (wire, wire % 3 == 0)).collect();
+
+        let mut sequential = GenericCircuitExecutor::new(reduction_tree(16));
+        let expected = sequential.run(&inputs).unwrap();
+
+        let mut circuit = reduction_tree(16);
+        circuit.reuse_memory_slots();
+        let components = circuit.components().len();
+        let mut executor = LevelExecutor::new(circuit);
+        executor.set_threshold(1).set_workers(4);
+        assert_eq!(executor.levels().len(), components);
+        assert!(executor.levels().iter().all(|level| level.len() == 1));
+        assert_eq!(executor.run(&inputs), Ok(expected));
+    }
+
+    #[test]
+    fn test_missing_input() {
+        let mut executor = LevelExecutor::new(reduction_tree(4));
+        executor.set_threshold(1);
+
+        let inputs = HashMap::from([(0, true), (1, true), (2, true), (5, true)]);
+        assert_eq!(
+            executor.run(&inputs),
+            Err(CircuitExecutionError::InputNotFoundError(3))
+        );
+    }
+}
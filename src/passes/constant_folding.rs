@@ -0,0 +1,260 @@
+//! # Constant Folding Module
+//!
+//! Contains the constant propagation and folding pass.
+
+use crate::{
+    circuit::GenericCircuit,
+    model::{Foldable, Folded},
+};
+use std::collections::{HashMap, HashSet};
+use thiserror::Error;
+
+/// Constant folding report.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct FoldReport {
+    /// Number of components removed because their outputs are constant or forward an input.
+    pub folded_components: usize,
+    /// Number of components replaced by simpler ones.
+    pub simplified_components: usize,
+    /// Number of constant components added to drive wires that are still read.
+    pub constant_drivers: usize,
+}
+
+/// Folding decision for a single component.
+enum Action<T> {
+    Keep(Vec<usize>),
+    Replace(T),
+    Remove,
+}
+
+impl<T, U> GenericCircuit<T, U>
+where
+    T: Foldable<U>,
+    U: Copy,
+{
+    /// Propagates the known values of some circuit inputs through the circuit, folding the
+    /// components whose outputs become constant and simplifying the others.
+    /// The constant inputs are removed from the circuit inputs. Constant wires that are still
+    /// read, or are circuit outputs, are driven by constant components.
+    /// The circuit is left untouched when an error is returned, apart from undoing
+    /// `reuse_memory_slots`.
+    pub fn fold_constants(
+        &mut self,
+        constants: &HashMap<usize, U>,
+    ) -> Result<FoldReport, ConstantFoldingError> {
+        self.split_reused_slots();
+        let memory_map = self.memory_map();
+        let mut known = HashMap::new();
+        for (wire, &value) in constants {
+            if !self.input_wires().contains(wire) {
+                return Err(ConstantFoldingError::NotAnInput(*wire));
+            }
+            let slot = memory_map
+                .get(wire)
+                .ok_or(ConstantFoldingError::NotAnInput(*wire))?;
+            known.insert(*slot, value);
+        }
+
+        let mut report = FoldReport::default();
+        let mut aliases = HashMap::new();
+        let mut read = HashSet::new();
+        let mut actions = Vec::with_capacity(self.components().len());
+
+        for component in self.components() {
+            let inputs = component
+                .inputs()
+                .iter()
+                .map(|slot| *aliases.get(slot).unwrap_or(slot))
+                .collect::<Vec<_>>();
+            let values = inputs
+                .iter()
+                .map(|slot| known.get(slot).copied())
+                .collect::<Vec<_>>();
+
+            let action = match component.fold(&values) {
+                Folded::Constant(values) if values.len() == component.outputs().len() => {
+                    known.extend(component.outputs().iter().copied().zip(values));
+                    report.folded_components += 1;
+                    Action::Remove
+                }
+                Folded::Identity(position)
+                    if component.outputs().len() == 1 && position < inputs.len() =>
+                {
+                    let (output, input) = (component.outputs()[0], inputs[position]);
+                    aliases.insert(output, input);
+                    if let Some(&value) = known.get(&input) {
+                        known.insert(output, value);
+                    }
+                    report.folded_components += 1;
+                    Action::Remove
+                }
+                Folded::Replaced(mut replacement) => {
+                    let replaced = replacement
+                        .inputs()
+                        .iter()
+                        .map(|slot| *aliases.get(slot).unwrap_or(slot))
+                        .collect::<Vec<_>>();
+                    read.extend(replaced.iter().copied());
+                    replacement.set_inputs(replaced);
+                    report.simplified_components += 1;
+                    Action::Replace(replacement)
+                }
+                _ => {
+                    read.extend(inputs.iter().copied());
+                    Action::Keep(inputs)
+                }
+            };
+            actions.push(action);
+        }
+
+        // Circuit outputs are read as well, through the aliases of their slots
+        read.extend(
+            self.output_wires()
+                .iter()
+                .filter_map(|wire| memory_map.get(wire))
+                .map(|slot| *aliases.get(slot).unwrap_or(slot)),
+        );
+
+        let mut driven = known
+            .iter()
+            .filter(|(slot, _)| read.contains(slot))
+            .map(|(&slot, &value)| (slot, value))
+            .collect::<Vec<_>>();
+        driven.sort_unstable_by_key(|&(slot, _)| slot);
+        let mut drivers = Vec::with_capacity(driven.len());
+        for (slot, value) in driven {
+            let driver = T::constant(value, slot).ok_or_else(|| {
+                let wire = memory_map
+                    .iter()
+                    .filter(|(_, &other)| other == slot)
+                    .map(|(&wire, _)| wire)
+                    .min()
+                    .unwrap_or(slot);
+                ConstantFoldingError::UnsupportedConstant(wire)
+            })?;
+            drivers.push(driver);
+        }
+        report.constant_drivers = drivers.len();
+
+        // Apply the decisions, constant drivers first
+        let components = std::mem::take(self.components_mut());
+        let mut folded = drivers;
+        for (mut component, action) in components.into_iter().zip(actions) {
+            match action {
+                Action::Keep(inputs) => {
+                    component.set_inputs(inputs);
+                    folded.push(component);
+                }
+                Action::Replace(replacement) => folded.push(replacement),
+                Action::Remove => {}
+            }
+        }
+        *self.components_mut() = folded;
+
+        for slot in self.memory_map_mut().values_mut() {
+            if let Some(&alias) = aliases.get(slot) {
+                *slot = alias;
+            }
+        }
+        self.remove_inputs(&constants.keys().copied().collect());
+        self.update_structure();
+
+        Ok(report)
+    }
+}
+
+#[derive(Debug, Error, PartialEq, Eq)]
+pub enum ConstantFoldingError {
+    #[error("Wire {0} is not a circuit input")]
+    NotAnInput(usize),
+    #[error("Constant wire {0} is still read, but the component type cannot drive constants")]
+    UnsupportedConstant(usize),
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        circuit::{CircuitBuilder, GenericCircuitExecutor},
+        gates::{
+            arithmetic::{ArithGate, ArithOperation, Field, PrimeField},
+            boolean::{tests::circuit, BooleanOperation},
+        },
+    };
+
+    #[test]
+    fn test_fold_boolean_constants() {
+        // Outputs (0 AND 1) XOR 2 on wire 4, and NOT (1 OR 2) on wire 6
+        let mut circuit = circuit::<bool>(
+            &[0, 1, 2],
+            &[4, 6],
+            [
+                (BooleanOperation::And, vec![0, 1], 3),
+                (BooleanOperation::Xor, vec![3, 2], 4),
+                (BooleanOperation::Or, vec![1, 2], 5),
+                (BooleanOperation::Not, vec![5], 6),
+            ],
+        );
+
+        // 1 = true turns the AND into a wire and makes the OR and NOT constant
+        let report = circuit.fold_constants(&HashMap::from([(1, true)])).unwrap();
+        assert_eq!(
+            report,
+            FoldReport {
+                folded_components: 3,
+                simplified_components: 0,
+                constant_drivers: 1,
+            }
+        );
+        assert_eq!(circuit.input_wires(), &[0, 2]);
+        assert_eq!(circuit.components().len(), 2);
+
+        let mut executor = GenericCircuitExecutor::new(circuit);
+        for (a, c) in [(false, false), (false, true), (true, false), (true, true)] {
+            let output = executor.run(&HashMap::from([(0, a), (2, c)])).unwrap();
+            assert_eq!(output, HashMap::from([(4, a ^ c), (6, false)]));
+        }
+    }
+
+    #[test]
+    fn test_fold_arithmetic_constants() {
+        type F = PrimeField<97>;
+
+        // Outputs (a * b) + c on wire 4
+        let mut builder = CircuitBuilder::<ArithGate<F>, F>::new();
+        builder.add_inputs(&[0, 1, 2]);
+        builder
+            .add_component(ArithGate::new(ArithOperation::Mul, vec![0, 1], vec![3]).unwrap())
+            .unwrap()
+            .add_component(ArithGate::new(ArithOperation::Add, vec![3, 2], vec![4]).unwrap())
+            .unwrap();
+        let mut circuit = builder.build().unwrap();
+
+        let report = circuit
+            .fold_constants(&HashMap::from([(1, F::new(5)), (2, F::zero())]))
+            .unwrap();
+        assert_eq!(
+            report,
+            FoldReport {
+                folded_components: 1,
+                simplified_components: 1,
+                constant_drivers: 0,
+            }
+        );
+
+        let mut executor = GenericCircuitExecutor::new(circuit);
+        let output = executor.run(&HashMap::from([(0, F::new(3))])).unwrap();
+        assert_eq!(output, HashMap::from([(4, F::new(15))]));
+    }
+
+    #[test]
+    fn test_fold_errors() {
+        let mut circuit = circuit::<bool>(&[0, 1], &[], [(BooleanOperation::And, vec![0, 1], 2)]);
+
+        assert_eq!(
+            circuit.fold_constants(&HashMap::from([(2, true)])),
+            Err(ConstantFoldingError::NotAnInput(2))
+        );
+        assert_eq!(circuit.input_wires(), &[0, 1]);
+    }
+}
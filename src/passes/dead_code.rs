@@ -0,0 +1,140 @@
+//! # Dead Code Module
+//!
+//! Contains the dead-code elimination pass.
+
+use crate::{circuit::GenericCircuit, model::Component};
+use std::collections::{HashMap, HashSet};
+
+/// Dead-code elimination report.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct DeadCodeReport {
+    /// Number of removed components.
+    pub removed_components: usize,
+    /// Number of removed wires.
+    pub removed_wires: usize,
+}
+
+impl<T, U> GenericCircuit<T, U>
+where
+    T: Component,
+    U: Copy,
+{
+    /// Removes the components that are not in the transitive fan-in of the circuit outputs,
+    /// then compacts the memory slots of the remaining wires.
+    /// Circuit inputs are kept even when unused, so the circuit interface does not change.
+    pub fn eliminate_dead_code(&mut self) -> DeadCodeReport {
+        self.split_reused_slots();
+        let memory_map = self.memory_map();
+        let mut needed = self
+            .output_wires()
+            .iter()
+            .filter_map(|wire| memory_map.get(wire).copied())
+            .collect::<HashSet<_>>();
+
+        // Walk the components backwards, so consumers are visited before their producers
+        let mut live = vec![false; self.components().len()];
+        for (index, component) in self.components().iter().enumerate().rev() {
+            if component.outputs().iter().any(|slot| needed.contains(slot)) {
+                live[index] = true;
+                needed.extend(component.inputs());
+            }
+        }
+
+        let mut kept = needed;
+        kept.extend(
+            self.input_wires()
+                .iter()
+                .filter_map(|wire| memory_map.get(wire).copied()),
+        );
+        let components_before = live.len();
+        let mut live = live.into_iter();
+        self.components_mut()
+            .retain(|_| live.next().unwrap_or(false));
+        for component in self.components() {
+            kept.extend(component.outputs());
+        }
+
+        // Renumber the kept slots contiguously, preserving their order
+        let mut slots = kept.into_iter().collect::<Vec<_>>();
+        slots.sort_unstable();
+        let remap = slots
+            .into_iter()
+            .enumerate()
+            .map(|(new, old)| (old, new))
+            .collect::<HashMap<_, _>>();
+
+        for component in self.components_mut() {
+            let inputs = component.inputs().iter().map(|slot| remap[slot]).collect();
+            let outputs = component.outputs().iter().map(|slot| remap[slot]).collect();
+            component.set_inputs(inputs);
+            component.set_outputs(outputs);
+        }
+
+        let memory_map = self.memory_map_mut();
+        let wires_before = memory_map.len();
+        memory_map.retain(|_, slot| match remap.get(slot) {
+            Some(&new) => {
+                *slot = new;
+                true
+            }
+            None => false,
+        });
+        self.update_structure();
+
+        DeadCodeReport {
+            removed_components: components_before - self.components().len(),
+            removed_wires: wires_before - self.memory_map().len(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        circuit::GenericCircuitExecutor,
+        gates::boolean::{tests::circuit, BooleanGate, BooleanOperation},
+    };
+
+    /// Circuit computing `(0 AND 1) XOR 2` on output 5, with a dead chain of three gates.
+    fn circuit_with_dead_code() -> GenericCircuit<BooleanGate, bool> {
+        circuit(
+            &[0, 1, 2],
+            &[5],
+            [
+                (BooleanOperation::And, vec![0, 1], 3),
+                (BooleanOperation::Or, vec![1, 2], 4),
+                (BooleanOperation::Not, vec![4], 6),
+                (BooleanOperation::Xor, vec![3, 2], 5),
+                (BooleanOperation::Nand, vec![6, 0], 7),
+            ],
+        )
+    }
+
+    #[test]
+    fn test_eliminate_dead_code() {
+        let inputs = HashMap::from([(0, true), (1, true), (2, false)]);
+        let expected = GenericCircuitExecutor::new(circuit_with_dead_code())
+            .run(&inputs)
+            .unwrap();
+
+        let mut circuit = circuit_with_dead_code();
+        assert_eq!(
+            circuit.eliminate_dead_code(),
+            DeadCodeReport {
+                removed_components: 3,
+                removed_wires: 3,
+            }
+        );
+        assert_eq!(circuit.components().len(), 2);
+        assert_eq!(circuit.memory_size(), 5);
+        assert!(!circuit.memory_map().contains_key(&4));
+        assert_eq!(circuit.input_wires(), &[0, 1, 2]);
+
+        // A second pass has nothing left to remove
+        assert_eq!(circuit.eliminate_dead_code(), DeadCodeReport::default());
+
+        let mut executor = GenericCircuitExecutor::new(circuit);
+        assert_eq!(executor.run(&inputs), Ok(expected));
+    }
+}
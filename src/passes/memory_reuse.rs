@@ -0,0 +1,322 @@
+//! # Memory Reuse Module
+//!
+//! Contains the wire-liveness based memory slot allocation pass.
+
+use crate::{
+    circuit::{GenericCircuit, WireLayout},
+    model::Component,
+};
+use std::{
+    cmp::Reverse,
+    collections::{BinaryHeap, HashMap, HashSet},
+};
+
+/// Memory reuse report.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct MemoryReuseReport {
+    /// Number of memory slots before the pass.
+    pub slots_before: usize,
+    /// Number of memory slots after the pass.
+    pub slots_after: usize,
+}
+
+impl<T, U> GenericCircuit<T, U>
+where
+    T: Component,
+    U: Copy,
+{
+    /// Reallocates the memory slots so that a slot is reused once every consumer of its wire
+    /// has executed, like a register allocator. Executors release the slots as they go.
+    /// Circuit input and output wires stay addressable by their original ids, and output slots
+    /// are never reused. Internal wires are no longer in the memory map.
+    /// The passes rewriting the circuit undo this one first, so it is best applied last.
+    pub fn reuse_memory_slots(&mut self) -> MemoryReuseReport {
+        self.split_reused_slots();
+        let slots_before = self.memory_size();
+        let memory_map = self.memory_map();
+        let layout = WireLayout {
+            slots: self
+                .components()
+                .iter()
+                .map(|component| (component.inputs().to_vec(), component.outputs().to_vec()))
+                .collect(),
+            memory_map: memory_map.clone(),
+        };
+
+        // Position of the last component reading each slot
+        let mut last_use = HashMap::new();
+        for (index, component) in self.components().iter().enumerate() {
+            for &slot in component.inputs() {
+                last_use.insert(slot, index);
+            }
+        }
+        let pinned = self
+            .output_wires()
+            .iter()
+            .filter_map(|wire| memory_map.get(wire).copied())
+            .collect::<HashSet<_>>();
+
+        let mut allocation = HashMap::new();
+        let mut free = BinaryHeap::new();
+        let mut next = 0;
+        let mut allocate = |free: &mut BinaryHeap<Reverse<usize>>| match free.pop() {
+            Some(Reverse(slot)) => slot,
+            None => {
+                next += 1;
+                next - 1
+            }
+        };
+
+        for wire in self.input_wires() {
+            if let Some(&slot) = memory_map.get(wire) {
+                allocation
+                    .entry(slot)
+                    .or_insert_with(|| allocate(&mut free));
+            }
+        }
+
+        let mut releases = Vec::with_capacity(self.components().len());
+        let mut rewired = Vec::with_capacity(self.components().len());
+        for (index, component) in self.components().iter().enumerate() {
+            let inputs = component
+                .inputs()
+                .iter()
+                .map(|slot| allocation[slot])
+                .collect::<Vec<_>>();
+
+            // Outputs are allocated before the inputs are released, as both are live while
+            // the component executes
+            let mut outputs = Vec::with_capacity(component.outputs().len());
+            for &slot in component.outputs() {
+                let new = allocate(&mut free);
+                allocation.insert(slot, new);
+                outputs.push(new);
+            }
+
+            let mut released = component
+                .inputs()
+                .iter()
+                .filter(|slot| last_use.get(slot) == Some(&index))
+                .chain(
+                    component
+                        .outputs()
+                        .iter()
+                        .filter(|slot| !last_use.contains_key(slot)),
+                )
+                .filter(|slot| !pinned.contains(slot))
+                .map(|slot| allocation[slot])
+                .collect::<Vec<_>>();
+            released.sort_unstable();
+            released.dedup();
+            free.extend(released.iter().map(|&slot| Reverse(slot)));
+
+            releases.push(released);
+            rewired.push((inputs, outputs));
+        }
+
+        let kept_wires = self
+            .input_wires()
+            .iter()
+            .chain(self.output_wires())
+            .copied()
+            .collect::<HashSet<_>>();
+        let memory_map = self.memory_map_mut();
+        memory_map.retain(|wire, _| kept_wires.contains(wire));
+        for slot in memory_map.values_mut() {
+            *slot = allocation[slot];
+        }
+
+        for (component, (inputs, outputs)) in self.components_mut().iter_mut().zip(rewired) {
+            component.set_inputs(inputs);
+            component.set_outputs(outputs);
+        }
+        self.set_releases(releases, layout);
+        self.update_structure();
+
+        MemoryReuseReport {
+            slots_before,
+            slots_after: self.memory_size(),
+        }
+    }
+
+    /// Undoes `reuse_memory_slots`, restoring the memory slots every wire had before and
+    /// dropping the slot releases. Every pass rewriting the circuit calls it first, as they
+    /// identify wires by their slot.
+    pub(crate) fn split_reused_slots(&mut self) {
+        let Some(layout) = self.take_releases() else {
+            return;
+        };
+
+        for (component, (inputs, outputs)) in self.components_mut().iter_mut().zip(layout.slots) {
+            component.set_inputs(inputs);
+            component.set_outputs(outputs);
+        }
+        *self.memory_map_mut() = layout.memory_map;
+        self.update_structure();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        circuit::{CircuitBuilder, CircuitExecutionError, GenericCircuitExecutor},
+        gates::{
+            arithmetic::{ArithGate, ArithGateError, ArithOperation, PrimeField},
+            boolean::{BooleanGate, BooleanOperation},
+        },
+        memory::BitMemory,
+    };
+
+    /// Chain of `length` XOR gates accumulating input 1 into input 0.
+    fn xor_chain(length: usize) -> GenericCircuit<BooleanGate, bool> {
+        let mut builder = CircuitBuilder::<BooleanGate, bool>::new();
+        builder.add_inputs(&[0, 1]);
+
+        let mut accumulator = 0;
+        for wire in 2..length + 2 {
+            builder
+                .add_component(
+                    BooleanGate::new(BooleanOperation::Xor, vec![accumulator, 1], vec![wire])
+                        .unwrap(),
+                )
+                .unwrap();
+            accumulator = wire;
+        }
+
+        builder.build().unwrap()
+    }
+
+    #[test]
+    fn test_reuse_memory_slots() {
+        let mut circuit = xor_chain(100);
+        let report = circuit.reuse_memory_slots();
+
+        assert_eq!(
+            report,
+            MemoryReuseReport {
+                slots_before: 102,
+                slots_after: 3,
+            }
+        );
+        assert_eq!(circuit.input_wires(), &[0, 1]);
+        assert_eq!(circuit.output_wires(), &[101]);
+        assert_eq!(circuit.memory_map().len(), 3);
+
+        let mut executor = GenericCircuitExecutor::new(circuit);
+        for (a, b) in [(false, true), (true, true), (true, false)] {
+            let output = executor.run(&HashMap::from([(0, a), (1, b)]));
+            assert_eq!(output, Ok(HashMap::from([(101, a)])));
+        }
+    }
+
+    #[test]
+    fn test_read_wire_after_reuse() {
+        // Computes `NOT (0 XOR 1)`, reusing the slot of input 0 for output 3
+        let mut builder = CircuitBuilder::<BooleanGate, bool>::new();
+        builder.add_inputs(&[0, 1]);
+        builder
+            .add_component(BooleanGate::new(BooleanOperation::Xor, vec![0, 1], vec![2]).unwrap())
+            .unwrap()
+            .add_component(BooleanGate::new(BooleanOperation::Not, vec![2], vec![3]).unwrap())
+            .unwrap();
+        let mut circuit = builder.build().unwrap();
+        circuit.reuse_memory_slots();
+        assert_eq!(circuit.memory_map()[&0], circuit.memory_map()[&3]);
+
+        let mut executor = GenericCircuitExecutor::new(circuit);
+        let output = executor.run(&HashMap::from([(0, true), (1, false)]));
+        assert_eq!(output, Ok(HashMap::from([(3, false)])));
+        assert_eq!(executor.read_wire(0), None);
+        assert_eq!(executor.read_wire(3), Some(false));
+    }
+
+    #[test]
+    fn test_wire_layout_after_reuse() {
+        let mut circuit = xor_chain(6);
+        let component_wires = circuit.component_wires();
+        let wire_map = circuit.wire_map().clone();
+
+        // Internal wires leave the memory map but keep their place in the wire layout
+        circuit.reuse_memory_slots();
+        assert_eq!(circuit.memory_map().len(), 3);
+        assert_eq!(circuit.wire_map(), &wire_map);
+        assert_eq!(circuit.component_wires(), component_wires);
+
+        circuit.split_reused_slots();
+        assert!(circuit.releases().is_empty());
+        assert_eq!(circuit.memory_map(), &wire_map);
+        assert_eq!(circuit.memory_size(), 8);
+    }
+
+    #[test]
+    fn test_execution_error_after_reuse() {
+        // Computes (a - b)^-1 * a, where the inverse reuses the slot of input b
+        type F = PrimeField<97>;
+        let gate = |op, inputs, output| ArithGate::<F>::new(op, inputs, vec![output]).unwrap();
+        let mut builder = CircuitBuilder::<ArithGate<F>, F>::new();
+        builder.add_inputs(&[0, 1]);
+        builder
+            .add_component(gate(ArithOperation::Sub, vec![0, 1], 2))
+            .unwrap()
+            .add_component(gate(ArithOperation::Inverse, vec![2], 3))
+            .unwrap()
+            .add_component(gate(ArithOperation::Mul, vec![3, 0], 4))
+            .unwrap();
+        let mut circuit = builder.build().unwrap();
+        circuit.reuse_memory_slots();
+
+        let mut executor = GenericCircuitExecutor::new(circuit);
+        let output = executor.run(&HashMap::from([(0, F::new(5)), (1, F::new(5))]));
+        assert_eq!(
+            output,
+            Err(CircuitExecutionError::ComponentExecutionError {
+                index: 1,
+                inputs: vec![2],
+                outputs: vec![3],
+                source: ArithGateError::DivisionByZero(2),
+            })
+        );
+    }
+
+    #[test]
+    fn test_reuse_with_bit_memory() {
+        let mut circuit = xor_chain(7);
+        circuit.reuse_memory_slots();
+
+        let mut executor = GenericCircuitExecutor::<_, _, BitMemory>::with_memory(circuit);
+        let output = executor.run(&HashMap::from([(0, false), (1, true)]));
+        assert_eq!(output, Ok(HashMap::from([(8, true)])));
+    }
+
+    #[test]
+    fn test_passes_after_reuse() {
+        let inputs = HashMap::from([(0, true), (1, true)]);
+
+        let mut circuit = xor_chain(5);
+        circuit.reuse_memory_slots();
+        let report = circuit.reuse_memory_slots();
+        assert_eq!(report.slots_after, 3);
+        let mut executor = GenericCircuitExecutor::new(circuit);
+        assert_eq!(executor.run(&inputs), Ok(HashMap::from([(6, false)])));
+
+        let mut circuit = xor_chain(5);
+        circuit.reuse_memory_slots();
+        circuit.eliminate_dead_code();
+        assert!(circuit.releases().is_empty());
+        assert_eq!(circuit.memory_size(), 7);
+        let mut executor = GenericCircuitExecutor::new(circuit);
+        assert_eq!(executor.run(&inputs), Ok(HashMap::from([(6, false)])));
+
+        // Input 1 folds to true, so the chain inverts input 0 five times
+        let mut circuit = xor_chain(5);
+        circuit.reuse_memory_slots();
+        circuit.fold_constants(&HashMap::from([(1, true)])).unwrap();
+        circuit.merge_common_subexpressions();
+        let mut executor = GenericCircuitExecutor::new(circuit);
+        assert_eq!(
+            executor.run(&HashMap::from([(0, true)])),
+            Ok(HashMap::from([(6, false)]))
+        );
+    }
+}
@@ -0,0 +1,8 @@
+//! # Passes Module
+//!
+//! Contains the circuit optimisation passes.
+
+pub mod constant_folding;
+pub mod dead_code;
+pub mod memory_reuse;
+pub mod structural_hashing;
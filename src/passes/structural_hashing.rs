@@ -0,0 +1,156 @@
+//! # Structural Hashing Module
+//!
+//! Contains the common subexpression elimination pass.
+
+use crate::{
+    circuit::{structural_signature, GenericCircuit},
+    model::StructuralHash,
+};
+use std::collections::HashMap;
+
+/// Common subexpression elimination report.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct StructuralHashReport {
+    /// Number of components merged into an equivalent component.
+    pub merged_components: usize,
+}
+
+impl<T, U> GenericCircuit<T, U>
+where
+    T: StructuralHash,
+    U: Copy,
+{
+    /// Merges the components computing the same function of the same wires, keeping the first
+    /// one in execution order. Consumers of the merged outputs, including circuit outputs, are
+    /// rewired to the outputs of the kept component.
+    pub fn merge_common_subexpressions(&mut self) -> StructuralHashReport {
+        self.split_reused_slots();
+        let mut report = StructuralHashReport::default();
+        let mut aliases = HashMap::new();
+        let mut signatures = HashMap::new();
+
+        let components = std::mem::take(self.components_mut());
+        let mut merged = Vec::with_capacity(components.len());
+        for mut component in components {
+            let inputs = component
+                .inputs()
+                .iter()
+                .map(|slot| *aliases.get(slot).unwrap_or(slot))
+                .collect::<Vec<_>>();
+            component.set_inputs(inputs);
+
+            let signature = structural_signature(&component, component.inputs());
+            match signatures.get(&signature) {
+                Some(&position) => {
+                    let existing: &T = &merged[position];
+                    aliases.extend(
+                        component
+                            .outputs()
+                            .iter()
+                            .copied()
+                            .zip(existing.outputs().iter().copied()),
+                    );
+                    report.merged_components += 1;
+                }
+                None => {
+                    signatures.insert(signature, merged.len());
+                    merged.push(component);
+                }
+            }
+        }
+        *self.components_mut() = merged;
+
+        for slot in self.memory_map_mut().values_mut() {
+            if let Some(&alias) = aliases.get(slot) {
+                *slot = alias;
+            }
+        }
+        self.update_structure();
+
+        report
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        circuit::{CircuitBuilder, GenericCircuitExecutor},
+        gates::boolean::{BooleanGate, BooleanOperation},
+    };
+
+    const GATES: [(BooleanOperation, [usize; 2], usize); 5] = [
+        (BooleanOperation::And, [0, 1], 2),
+        // Same as wire 2, with commuted inputs
+        (BooleanOperation::And, [1, 0], 3),
+        (BooleanOperation::Xor, [2, 0], 4),
+        // Same as wire 4 once wire 3 is merged into wire 2
+        (BooleanOperation::Xor, [3, 0], 5),
+        (BooleanOperation::Or, [4, 5], 6),
+    ];
+
+    fn gate(op: BooleanOperation, inputs: [usize; 2], output: usize) -> BooleanGate {
+        BooleanGate::new(op, inputs.to_vec(), vec![output]).unwrap()
+    }
+
+    fn expected_outputs() -> HashMap<usize, bool> {
+        HashMap::from([(5, false), (6, false)])
+    }
+
+    #[test]
+    fn test_merge_common_subexpressions() {
+        let mut builder = CircuitBuilder::<BooleanGate, bool>::new();
+        builder.add_inputs(&[0, 1]).add_outputs(&[5, 6]);
+        for (op, inputs, output) in GATES {
+            builder.add_component(gate(op, inputs, output)).unwrap();
+        }
+        let mut circuit = builder.build().unwrap();
+
+        assert_eq!(
+            circuit.merge_common_subexpressions(),
+            StructuralHashReport {
+                merged_components: 2,
+            }
+        );
+        assert_eq!(circuit.components().len(), 3);
+        assert_eq!(circuit.memory_map()[&5], circuit.memory_map()[&4]);
+
+        let mut executor = GenericCircuitExecutor::new(circuit);
+        let output = executor.run(&HashMap::from([(0, true), (1, true)]));
+        assert_eq!(output, Ok(expected_outputs()));
+    }
+
+    #[test]
+    fn test_hashed_builder() {
+        let mut builder = CircuitBuilder::<BooleanGate, bool>::new();
+        builder.add_inputs(&[0, 1]).add_outputs(&[5, 6]);
+        for (op, inputs, output) in GATES {
+            builder
+                .add_hashed_component(gate(op, inputs, output))
+                .unwrap();
+        }
+        let circuit = builder.build().unwrap();
+
+        assert_eq!(circuit.components().len(), 3);
+        assert_eq!(circuit.memory_map()[&3], circuit.memory_map()[&2]);
+
+        let mut executor = GenericCircuitExecutor::new(circuit);
+        let output = executor.run(&HashMap::from([(0, true), (1, true)]));
+        assert_eq!(output, Ok(expected_outputs()));
+    }
+
+    #[test]
+    fn test_hashed_builder_derived_outputs_order() {
+        let mut builder = CircuitBuilder::<BooleanGate, bool>::new();
+        builder.add_inputs(&[0, 1]);
+        for (inputs, output) in [([0, 1], 9), ([1, 0], 3)] {
+            builder
+                .add_hashed_component(gate(BooleanOperation::And, inputs, output))
+                .unwrap();
+        }
+
+        // Both wires share a memory slot, so they are ordered by wire id
+        let circuit = builder.build().unwrap();
+        assert_eq!(circuit.output_wires(), &[3, 9]);
+    }
+}
@@ -0,0 +1,498 @@
+//! # Sequential Module
+//!
+//! Contains the registers, the sequential circuit builder and the clocked executor.
+
+use crate::{
+    circuit::{
+        CircuitBuilder, CircuitBuilderError, CircuitExecutionError, CircuitMemory,
+        CircuitMemoryError, GenericCircuit, GenericCircuitExecutor, RunResult,
+    },
+    model::{Component, Executable, SizedMemory},
+    vcd::{VcdTrace, VcdValue},
+};
+use std::collections::HashMap;
+
+/// Register holding a wire value across clock cycles.
+/// During a cycle its output carries the value its input had at the end of the previous cycle,
+/// or the initial value during the first cycle.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Register<U> {
+    input: usize,
+    output: usize,
+    initial: U,
+}
+
+impl<U: Copy> Register<U> {
+    /// Creates a new register latching `input` into `output`.
+    pub fn new(input: usize, output: usize, initial: U) -> Self {
+        Self {
+            input,
+            output,
+            initial,
+        }
+    }
+
+    /// Returns the wire latched at the end of each cycle.
+    pub fn input(&self) -> usize {
+        self.input
+    }
+
+    /// Returns the wire carrying the register value during a cycle.
+    pub fn output(&self) -> usize {
+        self.output
+    }
+
+    /// Returns the register value during the first cycle.
+    pub fn initial(&self) -> U {
+        self.initial
+    }
+}
+
+/// Sequential circuit: a combinational circuit whose state is held by registers.
+/// The combinational circuit reads the register outputs as extra inputs and drives the
+/// register inputs as extra outputs.
+#[derive(Debug, PartialEq, Eq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(bound(
+        deserialize = "T: Component + serde::Deserialize<'de>, U: Copy + serde::Deserialize<'de>"
+    ))
+)]
+pub struct SequentialCircuit<T, U> {
+    circuit: GenericCircuit<T, U>,
+    registers: Vec<Register<U>>,
+    input_wires: Vec<usize>,
+    output_wires: Vec<usize>,
+}
+
+impl<T, U> SequentialCircuit<T, U>
+where
+    T: Component,
+    U: Copy,
+{
+    /// Returns the combinational circuit evaluated at each cycle.
+    pub fn circuit(&self) -> &GenericCircuit<T, U> {
+        &self.circuit
+    }
+
+    /// Returns the registers, in the order they were added.
+    pub fn registers(&self) -> &[Register<U>] {
+        &self.registers
+    }
+
+    /// Returns the original ids of the circuit inputs, without the register outputs.
+    pub fn input_wires(&self) -> &[usize] {
+        &self.input_wires
+    }
+
+    /// Returns the original ids of the circuit outputs, without the register inputs.
+    pub fn output_wires(&self) -> &[usize] {
+        &self.output_wires
+    }
+}
+
+/// Sequential circuit builder.
+/// Feedback is only permitted through registers: components may read register outputs, but
+/// the components themselves must still form an acyclic circuit.
+pub struct SequentialCircuitBuilder<T, U> {
+    builder: CircuitBuilder<T, U>,
+    registers: Vec<Register<U>>,
+    inputs: Vec<usize>,
+    outputs: Vec<usize>,
+}
+
+impl<T, U> SequentialCircuitBuilder<T, U>
+where
+    T: Component,
+    U: Copy,
+{
+    /// Creates a new sequential circuit builder.
+    /// Registers must be added before the components reading their outputs.
+    pub fn new() -> Self {
+        Self::with_builder(CircuitBuilder::new())
+    }
+
+    /// Creates a new sequential circuit builder that accepts components in any order.
+    pub fn new_deferred() -> Self {
+        Self::with_builder(CircuitBuilder::new_deferred())
+    }
+
+    fn with_builder(builder: CircuitBuilder<T, U>) -> Self {
+        Self {
+            builder,
+            registers: Vec::new(),
+            inputs: Vec::new(),
+            outputs: Vec::new(),
+        }
+    }
+
+    /// Adds circuit inputs to the builder.
+    pub fn add_inputs(&mut self, inputs: &[usize]) -> &mut Self {
+        self.builder.add_inputs(inputs);
+        self.inputs.extend(inputs.iter().copied());
+        self
+    }
+
+    /// Declares circuit outputs in the builder, keeping the declaration order.
+    /// Unlike combinational circuits, outputs are never inferred from unconsumed wires.
+    pub fn add_outputs(&mut self, outputs: &[usize]) -> &mut Self {
+        self.outputs.extend(outputs.iter().copied());
+        self
+    }
+
+    /// Adds a register to the builder.
+    /// Its output is an input of the combinational circuit, so it must be read by a component,
+    /// a register or a circuit output, and cannot be driven by anything else.
+    pub fn add_register(
+        &mut self,
+        register: Register<U>,
+    ) -> Result<&mut Self, CircuitBuilderError> {
+        let output = register.output();
+        if self.inputs.contains(&output) {
+            return Err(CircuitBuilderError::OutputIsACircuitInput(output));
+        }
+        if self.builder.drives(output) || self.registers.iter().any(|r| r.output() == output) {
+            return Err(CircuitBuilderError::OutputsConnection(output));
+        }
+
+        self.builder.add_inputs(&[output]);
+        self.registers.push(register);
+
+        Ok(self)
+    }
+
+    /// Adds a component to the builder.
+    pub fn add_component(&mut self, component: T) -> Result<&mut Self, CircuitBuilderError> {
+        self.builder.add_component(component)?;
+        Ok(self)
+    }
+
+    /// Builds the sequential circuit.
+    /// Register inputs must be driven by a component, a circuit input or a register output.
+    pub fn build(mut self) -> Result<SequentialCircuit<T, U>, CircuitBuilderError> {
+        let register_inputs = self
+            .registers
+            .iter()
+            .map(Register::input)
+            .collect::<Vec<_>>();
+        self.builder
+            .add_outputs(&self.outputs)
+            .add_outputs(&register_inputs);
+
+        Ok(SequentialCircuit {
+            circuit: self.builder.build()?,
+            registers: self.registers,
+            input_wires: self.inputs,
+            output_wires: self.outputs,
+        })
+    }
+}
+
+impl<T, U> Default for SequentialCircuitBuilder<T, U>
+where
+    T: Component,
+    U: Copy,
+{
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Clocked executor carrying the register state of a sequential circuit across cycles.
+/// Combinational wires are recomputed at every cycle.
+pub struct ClockedExecutor<T, U, M = CircuitMemory<U>> {
+    executor: GenericCircuitExecutor<T, U, M>,
+    registers: Vec<Register<U>>,
+    input_wires: Vec<usize>,
+    output_wires: Vec<usize>,
+    state: Vec<U>,
+    cycle: usize,
+}
+
+impl<T, U> ClockedExecutor<T, U>
+where
+    T: Component + Executable<U, CircuitMemory<U>>,
+    U: Copy,
+{
+    /// Creates a new clocked executor over a `CircuitMemory`.
+    pub fn new(circuit: SequentialCircuit<T, U>) -> Self {
+        Self::with_memory(circuit)
+    }
+}
+
+impl<T, U, M> ClockedExecutor<T, U, M>
+where
+    T: Component + Executable<U, M>,
+    U: Copy,
+    M: SizedMemory<U, Error = CircuitMemoryError>,
+{
+    /// Creates a new clocked executor over the memory type `M`, with registers in their
+    /// initial state.
+    pub fn with_memory(circuit: SequentialCircuit<T, U>) -> Self {
+        Self {
+            executor: GenericCircuitExecutor::with_memory(circuit.circuit),
+            state: circuit.registers.iter().map(Register::initial).collect(),
+            registers: circuit.registers,
+            input_wires: circuit.input_wires,
+            output_wires: circuit.output_wires,
+            cycle: 0,
+        }
+    }
+
+    /// Returns the number of completed cycles.
+    pub fn cycle(&self) -> usize {
+        self.cycle
+    }
+
+    /// Returns the current register values, by register output wire.
+    pub fn state(&self) -> HashMap<usize, U> {
+        self.registers
+            .iter()
+            .map(Register::output)
+            .zip(self.state.iter().copied())
+            .collect()
+    }
+
+    /// Returns the circuit input wires, without the register outputs.
+    pub fn input_wires(&self) -> &[usize] {
+        &self.input_wires
+    }
+
+    /// Puts the registers back in their initial state.
+    pub fn reset(&mut self) {
+        self.state = self.registers.iter().map(Register::initial).collect();
+        self.cycle = 0;
+    }
+
+    /// Executes one clock cycle using the provided input values and returns the output values.
+    /// The registers latch their inputs at the end of the cycle; they keep their state when an
+    /// error is returned.
+    pub fn step(&mut self, inputs: &HashMap<usize, U>) -> RunResult<U, T::Error> {
+        let mut values = inputs.clone();
+        values.extend(
+            self.registers
+                .iter()
+                .map(Register::output)
+                .zip(self.state.iter().copied()),
+        );
+        if values.len() != inputs.len() + self.registers.len() {
+            return Err(CircuitExecutionError::InputLengthMismatch);
+        }
+
+        let mut outputs = self.executor.run(&values)?;
+        for (value, register) in self.state.iter_mut().zip(&self.registers) {
+            *value = outputs[&register.input()];
+        }
+        outputs.retain(|wire, _| self.output_wires.contains(wire));
+        self.cycle += 1;
+
+        Ok(outputs)
+    }
+
+    /// Executes `cycles` clock cycles with the same input values and returns the output values
+    /// of each cycle, in order.
+    pub fn run_cycles(
+        &mut self,
+        cycles: usize,
+        inputs: &HashMap<usize, U>,
+    ) -> Result<Vec<HashMap<usize, U>>, CircuitExecutionError<T::Error>> {
+        (0..cycles).map(|_| self.step(inputs)).collect()
+    }
+}
+
+impl<T, U, M> ClockedExecutor<T, U, M>
+where
+    T: Component + Executable<U, M>,
+    U: VcdValue,
+    M: SizedMemory<U, Error = CircuitMemoryError>,
+{
+    /// Executes one clock cycle like `step`, recording every wire value of the cycle at the
+    /// current timestep of the trace, then advancing it.
+    pub fn step_traced(
+        &mut self,
+        inputs: &HashMap<usize, U>,
+        trace: &mut VcdTrace<U>,
+    ) -> RunResult<U, T::Error> {
+        let state = self.state();
+        let outputs = self.step(inputs)?;
+
+        // Values are recorded by wire id, so that traces of identical runs are identical
+        let mut values = inputs
+            .iter()
+            .chain(&state)
+            .map(|(&wire, &value)| (wire, value))
+            .collect::<Vec<_>>();
+        values.sort_unstable_by_key(|&(wire, _)| wire);
+        for (wire, value) in values {
+            trace.record(wire, value);
+        }
+        let circuit = self.executor.circuit();
+        let mut wires = circuit
+            .memory_map()
+            .keys()
+            .filter(|wire| !circuit.input_wires().contains(wire))
+            .copied()
+            .collect::<Vec<_>>();
+        wires.sort_unstable();
+        for wire in wires {
+            if let Some(value) = self.executor.read_wire(wire) {
+                trace.record(wire, value);
+            }
+        }
+        trace.advance();
+
+        Ok(outputs)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::gates::boolean::{BooleanGate, BooleanOperation};
+
+    fn gate(op: BooleanOperation, inputs: Vec<usize>, output: usize) -> BooleanGate {
+        BooleanGate::new(op, inputs, vec![output]).unwrap()
+    }
+
+    /// Two-bit counter with bits on wires 1 and 2, counting while input 0 is set.
+    fn counter() -> SequentialCircuit<BooleanGate, bool> {
+        let mut builder = SequentialCircuitBuilder::<BooleanGate, bool>::new();
+        builder.add_inputs(&[0]).add_outputs(&[1, 2]);
+        builder
+            .add_register(Register::new(3, 1, false))
+            .unwrap()
+            .add_register(Register::new(5, 2, false))
+            .unwrap();
+
+        let gates = [
+            (BooleanOperation::Xor, vec![1, 0], 3),
+            (BooleanOperation::And, vec![1, 0], 4),
+            (BooleanOperation::Xor, vec![2, 4], 5),
+        ];
+        for (op, inputs, output) in gates {
+            builder.add_component(gate(op, inputs, output)).unwrap();
+        }
+
+        builder.build().unwrap()
+    }
+
+    #[test]
+    fn test_run_cycles() {
+        let mut executor = ClockedExecutor::new(counter());
+        let enabled = HashMap::from([(0, true)]);
+
+        let outputs = executor.run_cycles(5, &enabled).unwrap();
+        let counts = outputs
+            .iter()
+            .map(|output| output[&1] as u8 + 2 * output[&2] as u8)
+            .collect::<Vec<_>>();
+        assert_eq!(counts, [0, 1, 2, 3, 0]);
+        assert_eq!(executor.cycle(), 5);
+        assert_eq!(executor.state(), HashMap::from([(1, true), (2, false)]));
+
+        // The state is held while counting is disabled
+        let output = executor.step(&HashMap::from([(0, false)])).unwrap();
+        assert_eq!(output, HashMap::from([(1, true), (2, false)]));
+        assert_eq!(executor.state(), HashMap::from([(1, true), (2, false)]));
+
+        executor.reset();
+        assert_eq!(executor.cycle(), 0);
+        assert_eq!(executor.state(), HashMap::from([(1, false), (2, false)]));
+    }
+
+    #[test]
+    fn test_step_errors() {
+        let mut executor = ClockedExecutor::new(counter());
+
+        assert_eq!(
+            executor.step(&HashMap::new()),
+            Err(CircuitExecutionError::InputLengthMismatch)
+        );
+        // Register outputs cannot be set as inputs
+        assert_eq!(
+            executor.step(&HashMap::from([(1, true)])),
+            Err(CircuitExecutionError::InputLengthMismatch)
+        );
+        assert_eq!(executor.cycle(), 0);
+    }
+
+    #[test]
+    fn test_builder_errors() {
+        let mut builder = SequentialCircuitBuilder::<BooleanGate, bool>::new();
+        builder.add_inputs(&[0]);
+        builder
+            .add_component(gate(BooleanOperation::Not, vec![0], 1))
+            .unwrap();
+
+        assert_eq!(
+            builder.add_register(Register::new(1, 0, false)).err(),
+            Some(CircuitBuilderError::OutputIsACircuitInput(0))
+        );
+        assert_eq!(
+            builder.add_register(Register::new(0, 1, false)).err(),
+            Some(CircuitBuilderError::OutputsConnection(1))
+        );
+
+        builder.add_register(Register::new(3, 2, false)).unwrap();
+        assert_eq!(
+            builder.add_register(Register::new(1, 2, true)).err(),
+            Some(CircuitBuilderError::OutputsConnection(2))
+        );
+        assert_eq!(
+            builder
+                .add_component(gate(BooleanOperation::Buf, vec![0], 2))
+                .err(),
+            Some(CircuitBuilderError::OutputIsACircuitInput(2))
+        );
+
+        // Register input 3 is never driven
+        builder
+            .add_component(gate(BooleanOperation::And, vec![1, 2], 4))
+            .unwrap();
+        assert_eq!(
+            builder.build().err(),
+            Some(CircuitBuilderError::UndrivenOutput(3))
+        );
+    }
+
+    #[test]
+    fn test_delay_line() {
+        // Register outputs only drive a circuit output or another register
+        let mut builder = SequentialCircuitBuilder::<BooleanGate, bool>::new();
+        builder.add_inputs(&[0]).add_outputs(&[2, 3]);
+        builder
+            .add_component(gate(BooleanOperation::Not, vec![0], 1))
+            .unwrap()
+            .add_register(Register::new(1, 2, false))
+            .unwrap()
+            .add_register(Register::new(2, 3, false))
+            .unwrap();
+
+        let mut executor = ClockedExecutor::new(builder.build().unwrap());
+        let outputs = executor
+            .run_cycles(3, &HashMap::from([(0, false)]))
+            .unwrap();
+        let expected = [(false, false), (true, false), (true, true)]
+            .map(|(delayed, twice)| HashMap::from([(2, delayed), (3, twice)]));
+        assert_eq!(outputs, expected);
+    }
+
+    #[test]
+    fn test_combinational_cycle() {
+        // Wires 1 and 2 feed each other without a register in between
+        let mut builder = SequentialCircuitBuilder::<BooleanGate, bool>::new_deferred();
+        builder.add_inputs(&[0]).add_outputs(&[2]);
+        builder
+            .add_component(gate(BooleanOperation::And, vec![0, 2], 1))
+            .unwrap()
+            .add_component(gate(BooleanOperation::Not, vec![1], 2))
+            .unwrap();
+
+        assert_eq!(
+            builder.build().err(),
+            Some(CircuitBuilderError::CycleError(vec![1, 2]))
+        );
+    }
+}
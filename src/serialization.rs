@@ -0,0 +1,272 @@
+//! # Serialization Module
+//!
+//! Contains the versioned JSON and binary encodings of circuits, builders and memories.
+
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use thiserror::Error;
+
+/// Version of the serialized data layout.
+/// Data written with a different version is rejected when deserializing.
+pub const FORMAT_VERSION: u32 = 2;
+
+#[derive(Serialize)]
+struct Envelope<'a, T> {
+    version: u32,
+    data: &'a T,
+}
+
+#[derive(Deserialize)]
+struct OwnedEnvelope<T> {
+    version: u32,
+    data: T,
+}
+
+/// Leading part of an envelope, read to tell data written with another version apart from
+/// invalid data.
+#[derive(Deserialize)]
+struct Header {
+    version: u32,
+}
+
+/// Serializes a value to JSON, tagged with the format version.
+pub fn to_json<T: Serialize>(value: &T) -> Result<String, SerializationError> {
+    Ok(serde_json::to_string(&Envelope {
+        version: FORMAT_VERSION,
+        data: value,
+    })?)
+}
+
+/// Deserializes a value from JSON, rejecting data written with another format version.
+pub fn from_json<T: DeserializeOwned>(json: &str) -> Result<T, SerializationError> {
+    match serde_json::from_str::<OwnedEnvelope<T>>(json) {
+        Ok(envelope) => check_version(envelope.version).map(|()| envelope.data),
+        Err(error) => {
+            check_version(serde_json::from_str::<Header>(json)?.version)?;
+            Err(error.into())
+        }
+    }
+}
+
+/// Serializes a value to a compact binary encoding, tagged with the format version.
+pub fn to_bytes<T: Serialize>(value: &T) -> Result<Vec<u8>, SerializationError> {
+    Ok(bincode::serialize(&Envelope {
+        version: FORMAT_VERSION,
+        data: value,
+    })?)
+}
+
+/// Deserializes a value from its binary encoding, rejecting data written with another format
+/// version.
+pub fn from_bytes<T: DeserializeOwned>(bytes: &[u8]) -> Result<T, SerializationError> {
+    match bincode::deserialize::<OwnedEnvelope<T>>(bytes) {
+        Ok(envelope) => check_version(envelope.version).map(|()| envelope.data),
+        Err(error) => {
+            check_version(bincode::deserialize::<Header>(bytes)?.version)?;
+            Err(error.into())
+        }
+    }
+}
+
+fn check_version(version: u32) -> Result<(), SerializationError> {
+    if version != FORMAT_VERSION {
+        return Err(SerializationError::VersionMismatch {
+            expected: FORMAT_VERSION,
+            found: version,
+        });
+    }
+    Ok(())
+}
+
+#[derive(Debug, Error)]
+pub enum SerializationError {
+    #[error("Serialized data has format version {found}, expected {expected}")]
+    VersionMismatch { expected: u32, found: u32 },
+    #[error("JSON error: {0}")]
+    JsonError(#[from] serde_json::Error),
+    #[error("Binary encoding error: {0}")]
+    BinaryError(#[from] bincode::Error),
+}
+
+/// Inconsistency found in deserialized data, which would otherwise misexecute or panic.
+#[derive(Debug, Error, PartialEq, Eq)]
+pub enum DataError {
+    #[error("Memory size is {found}, expected {expected} from the memory map and components")]
+    MemorySizeMismatch { expected: usize, found: usize },
+    #[error("Released memory slot {0} is out of bounds")]
+    SlotOutOfBounds(usize),
+    #[error("Slot releases do not match the circuit components")]
+    ReleasesMismatch,
+    #[error("Bit memory holds {found} words, expected {expected} for its size")]
+    WordCountMismatch { expected: usize, found: usize },
+    #[error("Bit memory word {0} holds values of uninitialized slots")]
+    UninitializedValue(usize),
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        circuit::{
+            CircuitBuilder, CircuitMemory, CircuitMemoryError, GenericCircuit,
+            GenericCircuitExecutor,
+        },
+        gates::boolean::{BooleanGate, BooleanOperation},
+        memory::BitMemory,
+        model::Memory,
+    };
+    use std::collections::HashMap;
+
+    fn builder() -> CircuitBuilder<BooleanGate, bool> {
+        let mut builder = CircuitBuilder::<BooleanGate, bool>::new();
+        builder.add_inputs(&[0, 1, 2]).add_outputs(&[4]);
+        builder
+            .add_component(BooleanGate::new(BooleanOperation::And, vec![0, 1], vec![3]).unwrap())
+            .unwrap()
+            .add_component(BooleanGate::new(BooleanOperation::Xor, vec![3, 2], vec![4]).unwrap())
+            .unwrap();
+        builder
+    }
+
+    #[test]
+    fn test_json_round_trip() {
+        let circuit = builder().build().unwrap();
+
+        let json = to_json(&circuit).unwrap();
+        let decoded: GenericCircuit<BooleanGate, bool> = from_json(&json).unwrap();
+        assert_eq!(decoded, circuit);
+
+        let mut executor = GenericCircuitExecutor::new(decoded);
+        let output = executor
+            .run(&HashMap::from([(0, true), (1, true), (2, false)]))
+            .unwrap();
+        assert_eq!(output, HashMap::from([(4, true)]));
+
+        let mut circuit = builder().build().unwrap();
+        circuit.reuse_memory_slots();
+        let json = to_json(&circuit).unwrap();
+        assert_eq!(from_json::<GenericCircuit<_, _>>(&json).unwrap(), circuit);
+    }
+
+    #[test]
+    fn test_binary_round_trip() {
+        let builder = builder();
+        let bytes = to_bytes(&builder).unwrap();
+        let decoded: CircuitBuilder<BooleanGate, bool> = from_bytes(&bytes).unwrap();
+        assert_eq!(decoded, builder);
+
+        let mut memory = CircuitMemory::new(2);
+        memory.write(1, true).unwrap();
+        let bytes = to_bytes(&memory).unwrap();
+        let decoded: CircuitMemory<bool> = from_bytes(&bytes).unwrap();
+        assert_eq!(decoded.read(1), Ok(true));
+        assert_eq!(
+            decoded.read(0),
+            Err(CircuitMemoryError::UninitializedSlot(0))
+        );
+    }
+
+    #[test]
+    fn test_version_mismatch() {
+        let circuit = builder().build().unwrap();
+
+        let json = to_json(&circuit).unwrap().replacen(
+            &format!("\"version\":{FORMAT_VERSION}"),
+            "\"version\":0",
+            1,
+        );
+        assert!(matches!(
+            from_json::<GenericCircuit<BooleanGate, bool>>(&json),
+            Err(SerializationError::VersionMismatch {
+                expected: FORMAT_VERSION,
+                found: 0,
+            })
+        ));
+
+        let mut bytes = to_bytes(&circuit).unwrap();
+        bytes[0] = bytes[0].wrapping_add(1);
+        assert!(matches!(
+            from_bytes::<GenericCircuit<BooleanGate, bool>>(&bytes),
+            Err(SerializationError::VersionMismatch { .. })
+        ));
+    }
+
+    #[test]
+    fn test_previous_version_rejected() {
+        // Version 1 lacked the slot releases and the wire layout of reused circuits
+        let json = to_json(&builder().build().unwrap()).unwrap().replacen(
+            "\"version\":2",
+            "\"version\":1",
+            1,
+        );
+        assert!(matches!(
+            from_json::<GenericCircuit<BooleanGate, bool>>(&json),
+            Err(SerializationError::VersionMismatch {
+                expected: 2,
+                found: 1,
+            })
+        ));
+    }
+
+    #[test]
+    fn test_structural_index_rebuilt() {
+        let mut builder = builder();
+        let and_gate = || BooleanGate::new(BooleanOperation::And, vec![1, 0], vec![5]).unwrap();
+        builder.add_hashed_component(and_gate()).unwrap();
+
+        let json = to_json(&builder).unwrap();
+        assert!(!json.contains("structural_index"));
+
+        // The loaded builder still merges components added before it was saved
+        let mut decoded: CircuitBuilder<BooleanGate, bool> = from_json(&json).unwrap();
+        decoded
+            .add_hashed_component(
+                BooleanGate::new(BooleanOperation::And, vec![0, 1], vec![6]).unwrap(),
+            )
+            .unwrap()
+            .add_outputs(&[5, 6]);
+        assert_eq!(decoded.build().unwrap().components().len(), 2);
+    }
+
+    #[test]
+    fn test_inconsistent_circuit_rejected() {
+        let json = to_json(&builder().build().unwrap()).unwrap();
+
+        let oversized = json.replacen("\"memory_size\":5", "\"memory_size\":2", 1);
+        let error = from_json::<GenericCircuit<BooleanGate, bool>>(&oversized).unwrap_err();
+        assert!(error
+            .to_string()
+            .contains("Memory size is 2, expected 5 from the memory map and components"));
+
+        let released = json.replacen("\"releases\":[]", "\"releases\":[[9],[]]", 1);
+        let error = from_json::<GenericCircuit<BooleanGate, bool>>(&released).unwrap_err();
+        assert!(error
+            .to_string()
+            .contains("Released memory slot 9 is out of bounds"));
+
+        let unlaid = json.replacen("\"releases\":[]", "\"releases\":[[0],[]]", 1);
+        let error = from_json::<GenericCircuit<BooleanGate, bool>>(&unlaid).unwrap_err();
+        assert!(error
+            .to_string()
+            .contains("Slot releases do not match the circuit components"));
+    }
+
+    #[test]
+    fn test_inconsistent_bit_memory_rejected() {
+        let mut memory = BitMemory::new(70);
+        memory.write(1, true).unwrap();
+        let json = to_json(&memory).unwrap();
+        assert_eq!(from_json::<BitMemory>(&json).unwrap(), memory);
+
+        let truncated = json.replacen("\"size\":70", "\"size\":200", 1);
+        let error = from_json::<BitMemory>(&truncated).unwrap_err();
+        assert!(error
+            .to_string()
+            .contains("Bit memory holds 2 words, expected 4 for its size"));
+
+        let uninitialized = json.replacen("\"initialized\":[2,", "\"initialized\":[0,", 1);
+        let error = from_json::<BitMemory>(&uninitialized).unwrap_err();
+        assert!(error
+            .to_string()
+            .contains("Bit memory word 0 holds values of uninitialized slots"));
+    }
+}
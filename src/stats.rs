@@ -0,0 +1,249 @@
+//! # Stats Module
+//!
+//! Contains the circuit statistics and cost report.
+
+use crate::{
+    circuit::GenericCircuit,
+    model::{Component, Kind},
+};
+use std::collections::{BTreeMap, HashMap};
+
+/// Circuit statistics report.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct CircuitStats {
+    /// Number of components of each kind, including the components of sub-circuits.
+    /// Only computed by `kind_stats`.
+    pub kind_counts: BTreeMap<String, usize>,
+    /// Total number of components. `kind_stats` counts the components of sub-circuits
+    /// instead of the sub-circuits themselves.
+    pub components: usize,
+    /// Number of multiplicative components, including those of sub-circuits.
+    /// Only computed by `kind_stats`.
+    pub multiplicative_components: usize,
+    /// Number of distinct wires, by original wire id.
+    pub wires: usize,
+    /// Number of memory slots needed to execute the circuit.
+    pub memory_size: usize,
+    /// Number of circuit inputs.
+    pub inputs: usize,
+    /// Number of circuit outputs.
+    pub outputs: usize,
+    /// Number of topological levels, where each sub-circuit is a single component.
+    pub depth: usize,
+    /// Largest number of multiplicative components on any path.
+    /// A path through a sub-circuit adds its whole multiplicative depth.
+    /// Only computed by `kind_stats`.
+    pub multiplicative_depth: usize,
+    /// Number of components on each topological level.
+    pub level_widths: Vec<usize>,
+    /// Number of wires for each fan-out, the number of component inputs reading a wire.
+    pub fan_out: BTreeMap<usize, usize>,
+}
+
+impl CircuitStats {
+    /// Returns the largest number of components on a single level.
+    pub fn max_width(&self) -> usize {
+        self.level_widths.iter().copied().max().unwrap_or(0)
+    }
+}
+
+impl<T, U> GenericCircuit<T, U>
+where
+    T: Component,
+    U: Copy,
+{
+    /// Computes the circuit statistics that do not depend on the component kinds.
+    /// Wires are counted by original wire id, whether or not memory slots are reused.
+    pub fn stats(&self) -> CircuitStats {
+        let mut stats = CircuitStats {
+            components: self.components().len(),
+            wires: self.wire_map().len(),
+            memory_size: self.memory_size(),
+            inputs: self.inputs().len(),
+            outputs: self.outputs().len(),
+            ..Default::default()
+        };
+
+        let levels = self.levels();
+        stats.depth = levels.iter().max().map_or(0, |&level| level + 1);
+        stats.level_widths = vec![0; stats.depth];
+        for &level in levels {
+            stats.level_widths[level] += 1;
+        }
+
+        // Wires that are never read have a fan-out of zero
+        let mut fan_out = self
+            .wire_map()
+            .keys()
+            .map(|&wire| (wire, 0))
+            .collect::<HashMap<_, usize>>();
+        for (inputs, _) in self.component_wires() {
+            for wire in inputs {
+                *fan_out.entry(wire).or_default() += 1;
+            }
+        }
+        for count in fan_out.into_values() {
+            *stats.fan_out.entry(count).or_default() += 1;
+        }
+
+        stats
+    }
+}
+
+impl<T, U> GenericCircuit<T, U>
+where
+    T: Component + Kind,
+    U: Copy,
+{
+    /// Computes the circuit statistics, along with the number of components of each kind and
+    /// the multiplicative depth. Sub-circuits are expanded into their own components.
+    pub fn kind_stats(&self) -> CircuitStats {
+        let mut stats = CircuitStats {
+            components: 0,
+            ..self.stats()
+        };
+
+        let mut multiplicative_depths = HashMap::new();
+        for component in self.components() {
+            let mut depth = component
+                .inputs()
+                .iter()
+                .filter_map(|input| multiplicative_depths.get(input))
+                .copied()
+                .max()
+                .unwrap_or(0);
+
+            match component.nested_stats() {
+                Some(nested) => {
+                    for (kind, count) in nested.kind_counts {
+                        *stats.kind_counts.entry(kind).or_default() += count;
+                    }
+                    stats.components += nested.components;
+                    stats.multiplicative_components += nested.multiplicative_components;
+                    depth += nested.multiplicative_depth;
+                }
+                None => {
+                    *stats
+                        .kind_counts
+                        .entry(component.kind().to_string())
+                        .or_default() += 1;
+                    stats.components += 1;
+                    if component.is_multiplicative() {
+                        stats.multiplicative_components += 1;
+                        depth += 1;
+                    }
+                }
+            }
+            stats.multiplicative_depth = stats.multiplicative_depth.max(depth);
+
+            for &output in component.outputs() {
+                multiplicative_depths.insert(output, depth);
+            }
+        }
+
+        stats
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        circuit::CircuitBuilder,
+        gates::boolean::{
+            tests::{circuit, full_adder},
+            BooleanGate, BooleanOperation,
+        },
+    };
+
+    #[test]
+    fn test_full_adder_stats() {
+        let stats = full_adder::<bool>().kind_stats();
+
+        assert_eq!(
+            stats.kind_counts,
+            BTreeMap::from([
+                ("AND".to_string(), 2),
+                ("OR".to_string(), 1),
+                ("XOR".to_string(), 2),
+            ])
+        );
+        assert_eq!(stats.components, 5);
+        assert_eq!(stats.multiplicative_components, 3);
+        assert_eq!(stats.wires, 8);
+        assert_eq!(stats.memory_size, 8);
+        assert_eq!(stats.inputs, 3);
+        assert_eq!(stats.outputs, 2);
+        assert_eq!(stats.depth, 3);
+        assert_eq!(stats.multiplicative_depth, 2);
+        assert_eq!(stats.level_widths, vec![2, 2, 1]);
+        assert_eq!(stats.max_width(), 2);
+        // Inputs 0, 1, 2 and wire 3 have a fan-out of two, wires 4 and 6 of one
+        assert_eq!(stats.fan_out, BTreeMap::from([(0, 2), (1, 2), (2, 4)]));
+    }
+
+    #[test]
+    fn test_nested_ripple_carry_adder_stats() {
+        // 2-bit ripple-carry adder: A bits 0 and 1, B bits 2 and 3, carry-in 4
+        let mut builder = CircuitBuilder::<GenericCircuit<BooleanGate, bool>, bool>::new();
+        builder.add_inputs(&[0, 1, 2, 3, 4]).add_outputs(&[5, 6, 8]);
+        for (bit, carry_in, sum, carry_out) in [(0, 4, 5, 7), (1, 7, 6, 8)] {
+            let mut adder = full_adder();
+            adder.set_inputs(vec![bit, bit + 2, carry_in]);
+            adder.set_outputs(vec![sum, carry_out]);
+            builder.add_component(adder).unwrap();
+        }
+
+        let stats = builder.build().unwrap().kind_stats();
+
+        assert_eq!(
+            stats.kind_counts,
+            BTreeMap::from([
+                ("AND".to_string(), 4),
+                ("OR".to_string(), 2),
+                ("XOR".to_string(), 4),
+            ])
+        );
+        assert_eq!(stats.components, 10);
+        assert_eq!(stats.multiplicative_components, 6);
+        assert_eq!(stats.depth, 2);
+        // The carry goes through both full adders
+        assert_eq!(stats.multiplicative_depth, 4);
+    }
+
+    #[test]
+    fn test_stats_without_kinds() {
+        let stats = circuit::<bool>(&[0, 1], &[], [(BooleanOperation::And, vec![0, 1], 2)]).stats();
+
+        assert!(stats.kind_counts.is_empty());
+        assert_eq!(stats.components, 1);
+        assert_eq!(stats.multiplicative_depth, 0);
+        assert_eq!(stats.fan_out, BTreeMap::from([(0, 1), (1, 2)]));
+    }
+
+    #[test]
+    fn test_stats_after_reuse() {
+        // Chain of six XOR gates accumulating input 1 into input 0
+        let gates = (2..8).map(|wire| {
+            let accumulator = if wire == 2 { 0 } else { wire - 1 };
+            (BooleanOperation::Xor, vec![accumulator, 1], wire)
+        });
+        let mut circuit = circuit::<bool>(&[0, 1], &[], gates);
+
+        let before = circuit.kind_stats();
+        assert_eq!(before.wires, 8);
+        assert_eq!(before.fan_out, BTreeMap::from([(0, 1), (1, 6), (6, 1)]));
+
+        // Only the memory size changes once slots are reused
+        circuit.reuse_memory_slots();
+        let after = circuit.kind_stats();
+        assert_eq!(after.memory_size, 3);
+        assert_eq!(
+            CircuitStats {
+                memory_size: before.memory_size,
+                ..after
+            },
+            before
+        );
+    }
+}
@@ -0,0 +1,359 @@
+//! # VCD Module
+//!
+//! Contains the Value Change Dump recorder for execution traces.
+
+use crate::{
+    circuit::{CircuitMemoryError, GenericCircuit, GenericCircuitExecutor, RunResult},
+    gates::{arithmetic::PrimeField, boolean::Trit},
+    model::{Component, Executable, SizedMemory},
+    observer::{ExecutionObserver, ObservedMemory},
+};
+use std::{
+    collections::{BTreeSet, HashMap, HashSet},
+    fmt::{self, Write},
+};
+
+/// Wire value that can be dumped in a VCD file.
+pub trait VcdValue: Copy + PartialEq {
+    /// Number of bits of the value.
+    const WIDTH: usize;
+
+    /// Writes the bits of the value, most significant first.
+    fn write_bits(&self, writer: &mut dyn Write) -> fmt::Result;
+}
+
+impl VcdValue for bool {
+    const WIDTH: usize = 1;
+
+    fn write_bits(&self, writer: &mut dyn Write) -> fmt::Result {
+        writer.write_char(if *self { '1' } else { '0' })
+    }
+}
+
+impl VcdValue for Trit {
+    const WIDTH: usize = 1;
+
+    fn write_bits(&self, writer: &mut dyn Write) -> fmt::Result {
+        writer.write_char(match self {
+            Trit::Zero => '0',
+            Trit::One => '1',
+            Trit::X => 'x',
+        })
+    }
+}
+
+macro_rules! impl_vcd_value {
+    ($($word:ty),*) => {
+        $(
+            impl VcdValue for $word {
+                const WIDTH: usize = <$word>::BITS as usize;
+
+                fn write_bits(&self, writer: &mut dyn Write) -> fmt::Result {
+                    write!(writer, "{:0width$b}", self, width = Self::WIDTH)
+                }
+            }
+        )*
+    };
+}
+
+impl_vcd_value!(u8, u16, u32, u64, u128);
+
+impl<const P: u64> VcdValue for PrimeField<P> {
+    const WIDTH: usize = 64;
+
+    fn write_bits(&self, writer: &mut dyn Write) -> fmt::Result {
+        self.value().write_bits(writer)
+    }
+}
+
+/// Execution trace recorder, written as a Value Change Dump for waveform viewers.
+/// Wires are named by their label, or by their original id.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct VcdTrace<U> {
+    labels: HashMap<usize, String>,
+    changes: Vec<(u64, usize, U)>,
+    time: u64,
+}
+
+impl<U: VcdValue> VcdTrace<U> {
+    /// Creates an empty trace starting at time 0.
+    pub fn new() -> Self {
+        Self {
+            labels: HashMap::new(),
+            changes: Vec::new(),
+            time: 0,
+        }
+    }
+
+    /// Names a wire in the dump, instead of its original id.
+    pub fn set_label(&mut self, wire: usize, label: &str) -> &mut Self {
+        self.labels.insert(wire, label.to_string());
+        self
+    }
+
+    /// Returns the current timestep.
+    pub fn time(&self) -> u64 {
+        self.time
+    }
+
+    /// Returns the recorded values as `(time, wire, value)`, in recording order.
+    pub fn changes(&self) -> &[(u64, usize, U)] {
+        &self.changes
+    }
+
+    /// Records the value of a wire at the current timestep.
+    pub fn record(&mut self, wire: usize, value: U) {
+        self.changes.push((self.time, wire, value));
+    }
+
+    /// Moves to the next timestep.
+    pub fn advance(&mut self) {
+        self.time += 1;
+    }
+
+    /// Writes the trace as a VCD file. Values equal to the previous value of their wire are
+    /// omitted, as are timesteps without any change.
+    pub fn write_vcd<W: Write>(&self, writer: &mut W) -> fmt::Result {
+        let wires = self
+            .changes
+            .iter()
+            .map(|&(_, wire, _)| wire)
+            .collect::<BTreeSet<_>>();
+        let codes = wires
+            .iter()
+            .enumerate()
+            .map(|(index, &wire)| (wire, identifier_code(index)))
+            .collect::<HashMap<_, _>>();
+
+        writeln!(writer, "$timescale 1ns $end")?;
+        writeln!(writer, "$scope module circuit $end")?;
+        for wire in &wires {
+            let name = match self.labels.get(wire) {
+                Some(label) => label.replace(char::is_whitespace, "_"),
+                None => format!("wire_{wire}"),
+            };
+            writeln!(writer, "$var wire {} {} {name} $end", U::WIDTH, codes[wire])?;
+        }
+        writeln!(writer, "$upscope $end")?;
+        writeln!(writer, "$enddefinitions $end")?;
+
+        let mut last = HashMap::new();
+        let mut time = None;
+        for &(at, wire, value) in &self.changes {
+            if last.insert(wire, value) == Some(value) {
+                continue;
+            }
+            if time != Some(at) {
+                writeln!(writer, "#{at}")?;
+                time = Some(at);
+            }
+
+            if U::WIDTH == 1 {
+                value.write_bits(writer)?;
+                writeln!(writer, "{}", codes[&wire])?;
+            } else {
+                writer.write_char('b')?;
+                value.write_bits(writer)?;
+                writeln!(writer, " {}", codes[&wire])?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+impl<U: VcdValue> Default for VcdTrace<U> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Observer recording the values written to mapped wires in a trace.
+/// Circuit input values are recorded at the current timestep, then each component execution
+/// advances the trace by one timestep before its output values are recorded.
+pub struct VcdObserver<'a, U> {
+    trace: &'a mut VcdTrace<U>,
+    slot_wires: HashMap<usize, Vec<usize>>,
+    input_wires: HashSet<usize>,
+    executing: bool,
+}
+
+impl<'a, U: VcdValue> VcdObserver<'a, U> {
+    /// Creates an observer recording the wires of the circuit in the trace, for a single run.
+    pub fn new<T: Component>(trace: &'a mut VcdTrace<U>, circuit: &GenericCircuit<T, U>) -> Self {
+        let mut slot_wires = HashMap::<usize, Vec<usize>>::new();
+        for (&wire, &slot) in circuit.memory_map() {
+            slot_wires.entry(slot).or_default().push(wire);
+        }
+        slot_wires
+            .values_mut()
+            .for_each(|wires| wires.sort_unstable());
+
+        Self {
+            trace,
+            slot_wires,
+            input_wires: circuit.input_wires().iter().copied().collect(),
+            executing: false,
+        }
+    }
+}
+
+impl<T, U: VcdValue> ExecutionObserver<T, U> for VcdObserver<'_, U> {
+    fn before_component(&mut self, _index: usize, _component: &T) {
+        self.executing = true;
+        self.trace.advance();
+    }
+
+    fn on_write(&mut self, slot: usize, value: U) {
+        // Reused input slots hold other wires once the components execute
+        for &wire in self.slot_wires.get(&slot).into_iter().flatten() {
+            if !self.executing || !self.input_wires.contains(&wire) {
+                self.trace.record(wire, value);
+            }
+        }
+    }
+}
+
+impl<T, U, M> GenericCircuitExecutor<T, U, M>
+where
+    T: Component + Executable<U, M>,
+    U: VcdValue,
+    M: SizedMemory<U, Error = CircuitMemoryError>,
+{
+    /// Runs the circuit like `run`, recording every mapped wire in the trace with a
+    /// `VcdObserver`, then advances the trace to the next timestep.
+    pub fn run_traced<E>(
+        &mut self,
+        inputs: &HashMap<usize, U>,
+        trace: &mut VcdTrace<U>,
+    ) -> RunResult<U, E>
+    where
+        T: for<'a, 'b> Executable<U, ObservedMemory<'a, T, M, VcdObserver<'b, U>>, Error = E>,
+    {
+        let mut observer = VcdObserver::new(trace, self.circuit());
+        let result = self.run_observed(inputs, &mut observer);
+        trace.advance();
+
+        result
+    }
+}
+
+/// Returns the short VCD identifier of the variable at `index`, using printable characters.
+fn identifier_code(mut index: usize) -> String {
+    const FIRST: u8 = b'!';
+    const COUNT: usize = (b'~' - b'!' + 1) as usize;
+
+    let mut code = String::new();
+    loop {
+        code.push((FIRST + (index % COUNT) as u8) as char);
+        index /= COUNT;
+        if index == 0 {
+            return code;
+        }
+        index -= 1;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        circuit::{CircuitBuilder, GenericCircuitExecutor},
+        gates::boolean::{BooleanGate, BooleanOperation},
+        sequential::{ClockedExecutor, Register, SequentialCircuitBuilder},
+    };
+
+    #[test]
+    fn test_identifier_codes() {
+        assert_eq!(identifier_code(0), "!");
+        assert_eq!(identifier_code(93), "~");
+        assert_eq!(identifier_code(94), "!!");
+        assert_eq!(identifier_code(95), "\"!");
+    }
+
+    #[test]
+    fn test_component_trace() {
+        let mut builder = CircuitBuilder::<BooleanGate, bool>::new();
+        builder.add_inputs(&[0, 1]);
+        builder
+            .add_component(BooleanGate::new(BooleanOperation::And, vec![0, 1], vec![2]).unwrap())
+            .unwrap()
+            .add_component(BooleanGate::new(BooleanOperation::Not, vec![2], vec![3]).unwrap())
+            .unwrap();
+
+        let mut executor = GenericCircuitExecutor::new(builder.build().unwrap());
+        let mut trace = VcdTrace::new();
+        trace.set_label(3, "nand out");
+        for (a, b) in [(true, true), (true, false)] {
+            executor
+                .run_traced(&HashMap::from([(0, a), (1, b)]), &mut trace)
+                .unwrap();
+        }
+        assert_eq!(trace.time(), 6);
+
+        let mut vcd = String::new();
+        trace.write_vcd(&mut vcd).unwrap();
+        let expected = "\
+$timescale 1ns $end
+$scope module circuit $end
+$var wire 1 ! wire_0 $end
+$var wire 1 \" wire_1 $end
+$var wire 1 # wire_2 $end
+$var wire 1 $ nand_out $end
+$upscope $end
+$enddefinitions $end
+#0
+1!
+1\"
+#1
+1#
+#2
+0$
+#3
+0\"
+#4
+0#
+#5
+1$
+";
+        assert_eq!(vcd, expected);
+    }
+
+    #[test]
+    fn test_cycle_trace() {
+        // Toggles wire 1 at every cycle while input 0 is set
+        let mut builder = SequentialCircuitBuilder::<BooleanGate, bool>::new();
+        builder.add_inputs(&[0]).add_outputs(&[1]);
+        builder.add_register(Register::new(2, 1, false)).unwrap();
+        builder
+            .add_component(BooleanGate::new(BooleanOperation::Xor, vec![1, 0], vec![2]).unwrap())
+            .unwrap();
+
+        let mut executor = ClockedExecutor::new(builder.build().unwrap());
+        let mut trace = VcdTrace::new();
+        for _ in 0..3 {
+            executor
+                .step_traced(&HashMap::from([(0, true)]), &mut trace)
+                .unwrap();
+        }
+
+        let values = |wire| {
+            trace
+                .changes()
+                .iter()
+                .filter(|&&(_, other, _)| other == wire)
+                .map(|&(time, _, value)| (time, value))
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(values(1), [(0, false), (1, true), (2, false)]);
+        assert_eq!(values(2), [(0, true), (1, false), (2, true)]);
+
+        // Every timestep records its wires in order
+        let wires = trace
+            .changes()
+            .iter()
+            .map(|&(time, wire, _)| (time, wire))
+            .collect::<Vec<_>>();
+        assert!(wires.is_sorted());
+    }
+}
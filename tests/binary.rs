@@ -127,6 +127,44 @@ impl FullAdder {
     }
 }
 
+// Ripple Carry Adder Circuit, built from full adders
+pub type RippleCarryAdderCircuit = GenericCircuit<FullAdderCircuit, bool>;
+
+#[derive(Debug, PartialEq, Eq)]
+pub struct RippleCarryAdder {
+    circuit: RippleCarryAdderCircuit,
+}
+
+impl RippleCarryAdder {
+    /// Inputs: A bits `0..width`, B bits `width..2 * width`, Carry-In `2 * width`.
+    /// Outputs: sum bits `2 * width + 1..3 * width + 1`, followed by the Carry-Out `4 * width`.
+    pub fn new(width: usize) -> Result<Self, CircuitBuilderError> {
+        let carry_in = 2 * width;
+        let sum = |bit: usize| carry_in + 1 + bit;
+        let carry = |bit: usize| carry_in + 1 + width + bit;
+
+        let mut builder = CircuitBuilder::<FullAdderCircuit, bool>::new();
+        builder.add_inputs(&(0..=carry_in).collect::<Vec<usize>>());
+
+        for bit in 0..width {
+            let mut adder = FullAdder::new()?.circuit;
+            let bit_carry_in = if bit == 0 { carry_in } else { carry(bit - 1) };
+
+            adder.set_inputs(vec![bit, width + bit, bit_carry_in]);
+            adder.set_outputs(vec![sum(bit), carry(bit)]);
+            builder.add_component(adder)?;
+        }
+
+        let mut outputs = (0..width).map(sum).collect::<Vec<usize>>();
+        outputs.push(carry(width - 1));
+        builder.add_outputs(&outputs);
+
+        Ok(Self {
+            circuit: builder.build()?,
+        })
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -144,4 +182,32 @@ mod tests {
         assert_eq!(output.get(&5), Some(&false)); // Sum = 0
         assert_eq!(output.get(&7), Some(&true)); // Carry = 1
     }
+
+    #[test]
+    fn test_ripple_carry_adder() {
+        let width = 4;
+        let adder = RippleCarryAdder::new(width).unwrap();
+        let mut executor = GenericCircuitExecutor::new(adder.circuit);
+
+        for a in 0..16usize {
+            for b in 0..16usize {
+                for carry_in in [false, true] {
+                    let mut input_values = HashMap::from([(2 * width, carry_in)]);
+                    for bit in 0..width {
+                        input_values.insert(bit, (a >> bit) & 1 == 1);
+                        input_values.insert(width + bit, (b >> bit) & 1 == 1);
+                    }
+
+                    let output = executor.run(&input_values).unwrap();
+
+                    // Sum bits, followed by the Carry-Out of the last full adder
+                    let result = (0..width)
+                        .map(|bit| usize::from(output[&(2 * width + 1 + bit)]) << bit)
+                        .sum::<usize>()
+                        + (usize::from(output[&(4 * width)]) << width);
+                    assert_eq!(result, a + b + usize::from(carry_in));
+                }
+            }
+        }
+    }
 }